@@ -1,60 +1,877 @@
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
-use std::fs::File;
-use std::io::prelude::*;
 use std::{
-    fs::{self, OpenOptions},
-    path::Path,
+    fs,
+    path::{Path, PathBuf},
     process::Command,
+    time::{Duration, Instant},
 };
 
 use crossterm::{
-    event::{self, Event, KeyCode, MouseEventKind},
+    event::{self, Event, KeyCode, KeyModifiers, MouseEventKind},
     terminal::size,
 };
-use headless_chrome::Browser;
-use scraper::{Html, Selector};
-use tui::{backend::Backend, Terminal};
-use urlencoding::encode;
+use tui::{backend::Backend, style::Color, Terminal};
+use urlencoding::{decode, encode};
 
 use crate::types::{APIAuthor, APIRank, KataAPI};
 use crate::{
+    datasource::DataSource,
+    paths,
     types::{
-        CodewarsCLI, CursorDirection, DownloadModalInput, InputMode, SettingsDatas, DIFFICULTY,
-        LANGAGE, SORT_BY, TAGS,
+        ChangedFiles, CodewarsCLI, CursorDirection, DailyKata, DiffLine, DownloadModalInput,
+        DownloadRecord, EditorScope, InputMode, InstructionFormat, KeyAction, MessageKind,
+        PreinstallHook, PromptKind, RetryAction, ScaffoldLevel, SearchStatus, SettingsDatas,
+        TodoEntry, UserKatas, ALL_LANGUAGES, DIFFICULTY, LANGAGE, SORT_BY, SPLIT_RATIO_BOUNDS,
+        TAGS,
     },
     ui::{ui, InputWidget, StatefulList},
     utils::{
-        fetch_codewars_api, fetch_html, get_uname, language_to_extension, ls_dir, open_url,
-        trim_specials_chars, write_file, TextMethods,
+        backup_corrupt_file, clear_log, codewars_base_url, codewars_token, comment_prefix,
+        copy_to_clipboard, diff_lines, expand_path, find_git_root, format_date, get_uname,
+        git_commit_kata, gitignore_for, is_kata_id, is_valid_url, kata_url, katas_to_csv,
+        katas_to_markdown, language_slug, language_to_extension, log_print, ls_dir,
+        markdown_to_html, markdown_to_text, offline_katas, open_url, parse_kata_ids,
+        push_unique_katas, rand_int, read_log_tail, register_settings_token,
+        reveal_in_file_manager, sanitize_query, save_offline_katas, sort_path_suggestions,
+        trim_specials_chars, unix_now, write_file, CopyOutcome, FetchError, RandomColors,
     },
-    TERMINAL_REF_SIZE,
+    TERMINAL_MIN_SIZE, TERMINAL_REF_SIZE,
 };
 
-const CODEWARS_ENDPOINT: &str = "https://www.codewars.com/kata/search";
+// downloaded katas, in the data dir
+const HISTORY_FILE: &str = "history.json";
+// last kata of the day, in the data dir
+const DAILY_FILE: &str = "daily.json";
+// ids of the solved katas, one per line, in the data dir
+const SOLVED_FILE: &str = "solved.txt";
+const TODO_FILE: &str = "todo.json";
+// hard limit of search pages fetched at once, to not hammer codewars
+const MAX_PAGES: usize = 10;
+// longer search queries are truncated
+pub const SEARCH_MAX_LEN: usize = 100;
+// how often the app wakes up without any event, and how long a success message stays
+const TICK_RATE: Duration = Duration::from_millis(250);
+const MESSAGE_DURATION: Duration = Duration::from_secs(5);
+// pause between the requests of save_offline, and before retrying a rate limited one
+const OFFLINE_FETCH_DELAY: Duration = Duration::from_millis(300);
+const OFFLINE_RATE_LIMIT_WAIT: Duration = Duration::from_secs(5);
+// start of the strings of copy_filters, to tell them apart when pasted
+const FILTERS_PREFIX: &str = "cw-filters:";
+// lines of the end of the log file shown by the log view
+const LOG_VIEW_MAX_LINES: usize = 500;
+// lines scrolled by PageUp/PageDown in the log view
+const LOG_VIEW_PAGE: usize = 10;
+
+// (label, keys, legend hint, help, action), run_app dispatches the keys and the legend and the help list them from here.
+// An empty label is a key of the line above, an empty hint keeps the key out of the legend
+pub type KeyMap = &'static [(
+    &'static str,
+    &'static [KeyCode],
+    &'static str,
+    &'static str,
+    KeyAction,
+)];
+
+pub const NORMAL_KEYMAP: KeyMap = &[
+    (
+        "s",
+        &[KeyCode::Char('s'), KeyCode::Char('S')],
+        "search",
+        "Edit the search query, Enter to search",
+        KeyAction::Focus(InputMode::Search),
+    ),
+    (
+        "l",
+        &[KeyCode::Char('l'), KeyCode::Char('L')],
+        "list",
+        "Focus List of Katas",
+        KeyAction::Focus(InputMode::KataList),
+    ),
+    (
+        "b/a/d/t",
+        &[KeyCode::Char('b'), KeyCode::Char('B')],
+        "sort/language/difficulty/tags",
+        "Go to Sort by/lAnguage/Difficulty/Tags",
+        KeyAction::Focus(InputMode::SortBy),
+    ),
+    (
+        "",
+        &[KeyCode::Char('a'), KeyCode::Char('A')],
+        "",
+        "",
+        KeyAction::Focus(InputMode::Langage),
+    ),
+    (
+        "",
+        &[KeyCode::Char('d'), KeyCode::Char('D')],
+        "",
+        "",
+        KeyAction::Focus(InputMode::Difficulty),
+    ),
+    (
+        "",
+        &[KeyCode::Char('t')],
+        "",
+        "",
+        KeyAction::Focus(InputMode::Tags),
+    ),
+    (
+        "Tab",
+        &[KeyCode::Tab],
+        "",
+        "Go to the search field",
+        KeyAction::Focus(InputMode::Search),
+    ),
+    (
+        "o",
+        &[KeyCode::Char('o'), KeyCode::Char('O')],
+        "collection",
+        "Open a collection",
+        KeyAction::OpenCollection,
+    ),
+    (
+        "g",
+        &[KeyCode::Char('g'), KeyCode::Char('G')],
+        "daily kata",
+        "Kata of the day",
+        KeyAction::KataOfTheDay,
+    ),
+    (
+        "T",
+        &[KeyCode::Char('T')],
+        "",
+        "List the to-dos",
+        KeyAction::Todos,
+    ),
+    (
+        "u/U",
+        &[KeyCode::Char('u')],
+        "",
+        "List my trained/authored katas, needs the api token",
+        KeyAction::ListUserKatas(UserKatas::Trained),
+    ),
+    (
+        "",
+        &[KeyCode::Char('U')],
+        "",
+        "",
+        KeyAction::ListUserKatas(UserKatas::Authored),
+    ),
+    (
+        "c/C",
+        &[KeyCode::Char('c')],
+        "",
+        "Copy the filters to share/Search shared filters",
+        KeyAction::CopyFilters,
+    ),
+    ("", &[KeyCode::Char('C')], "", "", KeyAction::ImportFilters),
+    (
+        "p/P",
+        &[KeyCode::Char('p')],
+        "",
+        "Pin/Clear default difficulty & tag",
+        KeyAction::PinFilters,
+    ),
+    ("", &[KeyCode::Char('P')], "", "", KeyAction::ClearFilters),
+    (
+        "i",
+        &[KeyCode::Char('i'), KeyCode::Char('I')],
+        "",
+        "Import solved katas from a file",
+        KeyAction::ImportSolved,
+    ),
+    (
+        "f",
+        &[KeyCode::Char('f'), KeyCode::Char('F')],
+        "",
+        "Hide/show the beta katas",
+        KeyAction::ToggleBeta,
+    ),
+    (
+        "k",
+        &[KeyCode::Char('k'), KeyCode::Char('K')],
+        "",
+        "View/clear the cache",
+        KeyAction::CacheView,
+    ),
+    (
+        "v",
+        &[KeyCode::Char('v'), KeyCode::Char('V')],
+        "",
+        "View/clear the logs",
+        KeyAction::LogView,
+    ),
+    (
+        "R",
+        &[KeyCode::Char('R')],
+        "",
+        "Reload the settings file",
+        KeyAction::ReloadSettings,
+    ),
+    (
+        "[ / ]",
+        &[KeyCode::Char('[')],
+        "",
+        "Narrow/widen this panel",
+        KeyAction::NarrowPanel,
+    ),
+    ("", &[KeyCode::Char(']')], "", "", KeyAction::WidenPanel),
+    (
+        "h",
+        &[KeyCode::Char('h'), KeyCode::Char('H')],
+        "",
+        "Collapse/expand this help",
+        KeyAction::ToggleHelp,
+    ),
+    (
+        "q",
+        &[KeyCode::Char('q')],
+        "quit",
+        "Quit app, Ctrl+C anywhere",
+        KeyAction::Quit,
+    ),
+];
+
+pub const KATA_LIST_KEYMAP: KeyMap = &[
+    (
+        "↑/↓",
+        &[KeyCode::Down],
+        "move",
+        "Move across the rows",
+        KeyAction::RowDown,
+    ),
+    ("", &[KeyCode::Up], "", "", KeyAction::RowUp),
+    (
+        "Tab/Shift+Tab",
+        &[KeyCode::Tab, KeyCode::Right],
+        "",
+        "Go to next/previous kata",
+        KeyAction::NextKata,
+    ),
+    (
+        "",
+        &[KeyCode::BackTab, KeyCode::Left],
+        "",
+        "",
+        KeyAction::PreviousKata,
+    ),
+    (
+        "Enter",
+        &[KeyCode::Enter],
+        "open",
+        "Open the kata in the browser",
+        KeyAction::OpenKata,
+    ),
+    (
+        "d",
+        &[KeyCode::Char('d'), KeyCode::Char('D')],
+        "download",
+        "Download selected Kata",
+        KeyAction::Download,
+    ),
+    (
+        "Space/Q",
+        &[KeyCode::Char(' ')],
+        "queue",
+        "Queue kata/Download the queue",
+        KeyAction::Queue,
+    ),
+    ("", &[KeyCode::Char('Q')], "", "", KeyAction::DownloadQueue),
+    (
+        "c",
+        &[KeyCode::Char('c'), KeyCode::Char('C')],
+        "copy",
+        "Copy kata instruction",
+        KeyAction::CopyDescription,
+    ),
+    (
+        "y",
+        &[KeyCode::Char('y'), KeyCode::Char('Y')],
+        "",
+        "Copy kata id",
+        KeyAction::CopyId,
+    ),
+    (
+        "n",
+        &[KeyCode::Char('n'), KeyCode::Char('N')],
+        "",
+        "Copy the names of the listed katas",
+        KeyAction::CopyNames,
+    ),
+    (
+        "r",
+        &[KeyCode::Char('r'), KeyCode::Char('R')],
+        "",
+        "Copy the train command/url",
+        KeyAction::CopyTrainCommand,
+    ),
+    (
+        "b",
+        &[KeyCode::Char('b'), KeyCode::Char('B')],
+        "",
+        "Copy sample tests as a code block",
+        KeyAction::CopySampleTests,
+    ),
+    (
+        "v",
+        &[KeyCode::Char('v'), KeyCode::Char('V')],
+        "solved",
+        "Mark kata as solved",
+        KeyAction::ToggleSolved,
+    ),
+    (
+        "l",
+        &[KeyCode::Char('l'), KeyCode::Char('L')],
+        "load more",
+        "Load more katas, the next page is added at the end",
+        KeyAction::LoadMore,
+    ),
+    (
+        "#",
+        &[KeyCode::Char('#')],
+        "",
+        "Show/hide the kata ids",
+        KeyAction::ToggleIds,
+    ),
+    (
+        "m/x",
+        &[KeyCode::Char('m'), KeyCode::Char('M')],
+        "",
+        "Add to the to-dos with a note/Remove from them",
+        KeyAction::AddTodo,
+    ),
+    (
+        "",
+        &[KeyCode::Char('x'), KeyCode::Char('X')],
+        "",
+        "",
+        KeyAction::RemoveTodo,
+    ),
+    (
+        "t",
+        &[KeyCode::Char('t'), KeyCode::Char('T')],
+        "",
+        "Try kata in a scratch file",
+        KeyAction::ScratchFile,
+    ),
+    (
+        "e",
+        &[KeyCode::Char('e'), KeyCode::Char('E')],
+        "",
+        "Export katas to markdown/csv",
+        KeyAction::Export,
+    ),
+    (
+        "s",
+        &[KeyCode::Char('s'), KeyCode::Char('S')],
+        "",
+        "Save the listed katas for offline reading, Esc to stop",
+        KeyAction::SaveOffline,
+    ),
+    (
+        "w",
+        &[KeyCode::Char('w'), KeyCode::Char('W')],
+        "",
+        "Open the downloaded kata folder",
+        KeyAction::RevealDownloaded,
+    ),
+    (
+        "a/A",
+        &[KeyCode::Char('a')],
+        "",
+        "Only this author katas/All authors",
+        KeyAction::AuthorFilter,
+    ),
+    ("", &[KeyCode::Char('A')], "", "", KeyAction::AllAuthors),
+    (
+        "u",
+        &[KeyCode::Char('u'), KeyCode::Char('U')],
+        "",
+        "Open the author profile",
+        KeyAction::AuthorProfile,
+    ),
+    (
+        "o",
+        &[KeyCode::Char('o'), KeyCode::Char('O')],
+        "",
+        "Open a collection",
+        KeyAction::OpenCollection,
+    ),
+    (
+        "i",
+        &[KeyCode::Char('i'), KeyCode::Char('I')],
+        "",
+        "Import solved katas from a file",
+        KeyAction::ImportSolved,
+    ),
+    (
+        "f",
+        &[KeyCode::Char('f'), KeyCode::Char('F')],
+        "",
+        "Hide/show the beta katas",
+        KeyAction::ToggleBeta,
+    ),
+    (
+        "[ / ]",
+        &[KeyCode::Char('[')],
+        "",
+        "Narrow/widen this panel",
+        KeyAction::NarrowPanel,
+    ),
+    ("", &[KeyCode::Char(']')], "", "", KeyAction::WidenPanel),
+    (
+        "Esc",
+        &[KeyCode::Esc],
+        "normal mode",
+        "Exit to normal mode",
+        KeyAction::Focus(InputMode::Normal),
+    ),
+];
+
+/// action bound to `code` in `keymap`
+pub fn keymap_action(keymap: KeyMap, code: KeyCode) -> Option<KeyAction> {
+    keymap
+        .iter()
+        .find(|(_, keys, ..)| keys.contains(&code))
+        .map(|(.., action)| *action)
+}
+
+/// (label, hint) of the keys of `keymap` shown in the legend
+fn keymap_hints(keymap: KeyMap) -> Vec<(&'static str, &'static str)> {
+    keymap
+        .iter()
+        .filter(|(label, _, hint, ..)| label.len() > 0 && hint.len() > 0)
+        .map(|(label, _, hint, ..)| (*label, *hint))
+        .collect()
+}
 
 impl CodewarsCLI {
-    pub fn new() -> CodewarsCLI {
+    pub fn new(source: Box<dyn DataSource>) -> CodewarsCLI {
         CodewarsCLI {
+            source,
+            clipboard: None,
             input_mode: InputMode::Normal,
+            message: None,
+            message_expiry: None,
+            retry: None,
+            prompt: None,
+            cache_view: None,
+            log_view: None,
+            overwrite_diff: None,
             settings: Settings::load(),
             terminal_size: (0, 0),
+            split_ratio: SettingsDatas::default().split_ratio,
+            list_columns: 1,
+            grid_columns: 1,
+            wrap_navigation: true,
+            help_collapsed: false,
+            mouse: true,
+            quit_requested: false,
+            banner_colors: Box::new(RandomColors),
+            banner: None,
+            // https://no-color.org: set and not empty
+            no_color: std::env::var("NO_COLOR")
+                .map(|v| v.len() > 0)
+                .unwrap_or(false),
             field_dropdown: (false, StatefulList::with_items(vec![], 0)),
+            dropdown_filter: InputWidget::default(),
+            dropdown_sorted: false,
+            field_areas: vec![],
             download_modal: (DownloadModalInput::Disabled, 0),
+            download_warning: None,
+            download_queue: vec![],
+            queue_modal: false,
+            download_progress: None,
             download_path: InputWidget::default(),
             editor_field: InputWidget::default(),
+            instruction_format: InstructionFormat::Markdown,
             download_langage: (false, StatefulList::with_items(vec![], 0)),
+            download_langage_locked: false,
+            languages_cache: HashMap::new(),
+            languages_loading: false,
+            template_previews: HashMap::new(),
+            fetched_templates: HashMap::new(),
+            template_preview: false,
+            template_preview_loading: false,
             search_result: StatefulList::with_items(vec![], 0),
+            search_status: SearchStatus::NeverSearched,
+            last_submitted_query: None,
             search_field: InputWidget::default(),
+            author_filter: None,
+            hide_beta: false,
+            collection: None,
+            todos: vec![],
+            todo_view: false,
+            show_ids: false,
+            user_view: None,
+            field_hints: Default::default(),
+            loaded_pages: 0,
+            solved: HashSet::new(),
             sortby_field: 0,
+            sort_reversed: false,
             langage_field: 0,
             difficulty_field: 0,
             tag_field: 0,
         }
     }
 
+    /// read the settings file again (e.g: after editing it by hand) and apply it to the running app
+    pub fn reload_settings(&mut self) {
+        let settings = match self.settings.fetch_and_cache() {
+            Ok(settings) => settings,
+            Err(why) => {
+                return self.set_message(
+                    MessageKind::Error,
+                    format!("Failed to reload the settings: {why}"),
+                )
+            }
+        };
+
+        self.split_ratio = settings
+            .split_ratio
+            .clamp(SPLIT_RATIO_BOUNDS.0, SPLIT_RATIO_BOUNDS.1);
+        self.wrap_navigation = settings.wrap_navigation;
+        self.list_columns = settings.list_columns.max(1);
+        self.help_collapsed = settings.help_collapsed;
+        register_settings_token(&settings.api_token);
+        self.source.configure(&settings);
+        if self.download_modal.0 == DownloadModalInput::Disabled {
+            self.download_path = InputWidget::default();
+            self.download_path.push_str(&settings.download_path);
+            self.editor_field = InputWidget::default();
+            self.editor_field.push_str(&settings.editor_command);
+            self.instruction_format = settings.instruction_format;
+        }
+        self.refresh_field_hints();
+        self.set_message(MessageKind::Success, "Settings reloaded".to_string());
+        self.warn_recovered_settings();
+    }
+
+    /// tell the user their corrupt settings were replaced by the defaults, and where they are kept
+    pub fn warn_recovered_settings(&mut self) {
+        if let Some(backup) = self.settings.take_recovered() {
+            self.set_message(
+                MessageKind::Error,
+                format!(
+                    "The settings file was corrupt, the defaults are used. Yours is kept in {}",
+                    backup.display()
+                ),
+            );
+        }
+    }
+
+    /// preselect the difficulty and tag saved in the settings, out of range indexes are ignored
+    pub fn apply_default_filters(&mut self, settings: &SettingsDatas) {
+        if settings.default_difficulty < DIFFICULTY.len() {
+            self.difficulty_field = settings.default_difficulty;
+        }
+        if settings.default_tag < TAGS.len() {
+            self.tag_field = settings.default_tag;
+        }
+    }
+
+    /// save the current difficulty and tag as the startup ones, or clear them (back to no filter)
+    pub fn save_default_filters(&mut self, clear: bool) {
+        let (difficulty, tag) = if clear {
+            (0, 0)
+        } else {
+            (self.difficulty_field, self.tag_field)
+        };
+
+        let old_settings = self.settings.value().unwrap_or_default();
+        match self.settings.set(&SettingsDatas {
+            default_difficulty: difficulty,
+            default_tag: tag,
+            ..old_settings
+        }) {
+            Ok(_) if clear => {
+                self.set_message(MessageKind::Success, "Default filters cleared".to_string())
+            }
+            Ok(_) => self.set_message(
+                MessageKind::Success,
+                format!(
+                    "Default filters saved: {}, {}",
+                    DIFFICULTY[difficulty], TAGS[tag]
+                ),
+            ),
+            Err(why) => self.set_message(
+                MessageKind::Error,
+                format!("Failed to save the default filters: {why}"),
+            ),
+        }
+    }
+
+    /// widen (or narrow with a negative `delta`) the search section, and save it
+    pub fn resize_split(&mut self, delta: i16) {
+        let ratio = (self.split_ratio as i16 + delta)
+            .clamp(SPLIT_RATIO_BOUNDS.0 as i16, SPLIT_RATIO_BOUNDS.1 as i16)
+            as u16;
+        if ratio == self.split_ratio {
+            return;
+        }
+        self.split_ratio = ratio;
+
+        let old_settings = self.settings.value().unwrap_or_default();
+        if let Err(_) = self.settings.set(&SettingsDatas {
+            split_ratio: ratio,
+            ..old_settings
+        }) {}
+    }
+
+    /// collapse the keys help to a line, or expand it back, and save it
+    pub fn toggle_help(&mut self) {
+        self.help_collapsed = !self.help_collapsed;
+
+        let old_settings = self.settings.value().unwrap_or_default();
+        if let Err(_) = self.settings.set(&SettingsDatas {
+            help_collapsed: self.help_collapsed,
+            ..old_settings
+        }) {}
+    }
+
+    /// colors of the banner words, the same ones until `banner` is reset: the app redraws on every tick
+    pub fn banner_colors(&mut self) -> [Color; 3] {
+        if let Some(colors) = self.banner {
+            return colors;
+        }
+        let colors = [
+            self.banner_colors.next_color(),
+            self.banner_colors.next_color(),
+            self.banner_colors.next_color(),
+        ];
+        self.banner = Some(colors);
+        colors
+    }
+
+    /// what the keys currently act on, e.g: "Search" or "Download › Path"
+    pub fn mode_label(&self) -> String {
+        if self.overwrite_diff.is_some() {
+            return "Download › Overwrite?".to_string();
+        }
+        if self.cache_view.is_some() {
+            return "Cache".to_string();
+        }
+        if self.log_view.is_some() {
+            return "Logs".to_string();
+        }
+        if self.prompt.is_some() {
+            return "Prompt".to_string();
+        }
+
+        let modal = match self.download_modal.0 {
+            DownloadModalInput::Disabled => None,
+            DownloadModalInput::Langage => Some("Language"),
+            DownloadModalInput::Path => Some("Path"),
+            DownloadModalInput::Editor => Some("Editor"),
+            DownloadModalInput::Submit => Some("Submit"),
+        };
+        if let Some(step) = modal {
+            return format!("Download › {step}");
+        }
+
+        let mode = match self.input_mode {
+            InputMode::Normal => "Normal",
+            InputMode::Search => "Search",
+            InputMode::SortBy => "Sort By",
+            InputMode::Langage => "Language",
+            InputMode::Difficulty => "Difficulty",
+            InputMode::Tags => "Tags",
+            InputMode::KataList => "List",
+        };
+        if self.field_dropdown.0 {
+            return format!("{mode} › Dropdown");
+        }
+        mode.to_string()
+    }
+
+    /// mouse wheel, like Up/Down on the open dropdown, or on the kata list when the cursor is over it
+    /// it never wraps, a wheel spin would go round the list
+    pub fn scroll(&mut self, down: bool, column: u16) {
+        fn step<T>(list: &mut StatefulList<T>, down: bool) {
+            match down {
+                true => list.step_next(false),
+                false => list.step_previous(false),
+            }
+        }
+
+        let over_list =
+            column as u32 >= self.terminal_size.0 as u32 * self.split_ratio as u32 / 100;
+        if self.overwrite_diff.is_some() {
+            self.scroll_diff(down)
+        } else if self.log_view.is_some() {
+            self.scroll_logs(!down, 1)
+        } else if self.field_dropdown.0 {
+            step(&mut self.field_dropdown.1, down)
+        } else if self.download_langage.0 {
+            step(&mut self.download_langage.1, down)
+        } else if over_list
+            && self.download_modal.0 == DownloadModalInput::Disabled
+            && self.cache_view.is_none()
+            && self.log_view.is_none()
+            && self.prompt.is_none()
+            && self.search_result.items.len() > 0
+        {
+            step(&mut self.search_result, down)
+        }
+    }
+
+    /// scroll the overwrite diff by one line
+    pub fn scroll_diff(&mut self, down: bool) {
+        if let Some((files, scroll)) = &mut self.overwrite_diff {
+            let total = files.iter().map(|(_, diff)| diff.len() + 1).sum::<usize>();
+            *scroll = match down {
+                true => (*scroll + 1).min(total.saturating_sub(1)),
+                false => scroll.saturating_sub(1),
+            };
+        }
+    }
+
+    /// keys handled right now by run_app, from the keymaps in normal mode and in the list of katas
+    pub fn key_hints(&self) -> Vec<(&'static str, &'static str)> {
+        if self.overwrite_diff.is_some() {
+            return vec![
+                ("y", "overwrite"),
+                ("↑/↓", "scroll"),
+                ("Esc", "keep my files"),
+            ];
+        }
+        if self.cache_view.is_some() {
+            return vec![("x", "clear"), ("Esc", "close")];
+        }
+        if self.log_view.is_some() {
+            return vec![
+                ("↑/↓", "scroll"),
+                ("PgUp/PgDn", "page"),
+                ("x", "clear"),
+                ("Esc", "close"),
+            ];
+        }
+        if self.prompt.is_some() {
+            return vec![("Enter", "confirm"), ("Esc", "cancel")];
+        }
+        if self.field_dropdown.0 {
+            let mut hints = vec![("↑/↓", "move"), ("Enter", "select")];
+            if self.is_dropdown_searchable() {
+                hints.push(("type", "filter"));
+                hints.push(("Tab", "sort"));
+            }
+            hints.push(("Esc", "cancel"));
+            return hints;
+        }
+
+        match self.input_mode {
+            InputMode::Normal if self.search_status == SearchStatus::Empty => {
+                let mut hints = vec![("Ctrl+B", "broaden the search")];
+                hints.extend(keymap_hints(NORMAL_KEYMAP));
+                hints
+            }
+            InputMode::Normal => keymap_hints(NORMAL_KEYMAP),
+            InputMode::Search => vec![
+                ("Enter", "search"),
+                ("Tab", "next field"),
+                ("Esc", "normal mode"),
+            ],
+            InputMode::SortBy => vec![
+                ("Enter", "choose"),
+                ("r", "reverse"),
+                ("Tab/Shift+Tab", "fields"),
+                ("Esc", "normal mode"),
+            ],
+            InputMode::Langage | InputMode::Difficulty | InputMode::Tags => {
+                vec![
+                    ("Enter", "choose"),
+                    ("Tab/Shift+Tab", "fields"),
+                    ("Esc", "normal mode"),
+                ]
+            }
+            InputMode::KataList => match self.download_modal.0 {
+                DownloadModalInput::Disabled => keymap_hints(KATA_LIST_KEYMAP),
+                DownloadModalInput::Langage if self.download_langage.0 => {
+                    vec![("↑/↓", "move"), ("Enter/Esc", "done")]
+                }
+                DownloadModalInput::Langage => vec![
+                    ("Enter", "languages"),
+                    ("u", "unlock filter"),
+                    ("p", "preview"),
+                    ("Tab", "next"),
+                    ("Esc", "close"),
+                ],
+                DownloadModalInput::Path => vec![
+                    ("Tab/Shift+Tab", "suggestions"),
+                    ("Right/Enter", "accept"),
+                    ("↑/↓", "fields"),
+                    ("Esc", "close"),
+                ],
+                DownloadModalInput::Editor => vec![("Tab/Shift+Tab", "fields"), ("Esc", "close")],
+                DownloadModalInput::Submit => vec![
+                    ("Enter", "download"),
+                    ("f", "instruction format"),
+                    ("p", "preview"),
+                    ("Shift+Tab", "back"),
+                    ("Esc", "close"),
+                ],
+            },
+        }
+    }
+
+    /// hint of a search field, see `refresh_field_hints`
+    pub fn field_hint(&self, field: &InputMode) -> Option<&str> {
+        let idx = match field {
+            InputMode::Search => 0,
+            InputMode::SortBy => 1,
+            InputMode::Langage => 2,
+            InputMode::Difficulty => 3,
+            InputMode::Tags => 4,
+            _ => return None,
+        };
+        self.field_hints[idx].as_deref()
+    }
+
+    /// point at the field that doesn't work as is, or that empties the search
+    pub fn refresh_field_hints(&mut self) {
+        let mut hints: [Option<String>; 5] = Default::default();
+
+        if LANGAGE[self.langage_field] == "My Languages" && self.my_languages_preset().is_none() {
+            hints[2] = Some("requires login, or set my_languages".to_string());
+        }
+
+        let searched = self.collection.is_none() && !self.todo_view && self.user_view.is_none();
+        if searched && self.search_status == SearchStatus::Empty {
+            match broadened_filters(self.tag_field, self.difficulty_field, self.langage_field) {
+                Some((_, "tag")) => hints[4] = Some("no results, Ctrl+B drops it".to_string()),
+                Some((_, "difficulty")) => {
+                    hints[3] = Some("no results, Ctrl+B drops it".to_string())
+                }
+                Some(_) if hints[2].is_none() => {
+                    hints[2] = Some("no results, Ctrl+B drops it".to_string())
+                }
+                Some(_) => {}
+                None => hints[0] = Some("no results".to_string()),
+            }
+        }
+        self.field_hints = hints;
+    }
+
+    /// search field under a cell, from the areas of the last draw (the help can be collapsed)
+    pub fn field_at(&self, column: u16, row: u16) -> Option<InputMode> {
+        self.field_areas
+            .iter()
+            .find(|(_, area)| {
+                (area.left()..area.right()).contains(&column)
+                    && (area.top()..area.bottom()).contains(&row)
+            })
+            .map(|(mode, _)| *mode)
+    }
+
     pub fn change_state(&mut self, new_state: InputMode) {
         self.input_mode = new_state;
 
+        // the last query is kept, ready to be edited
+        if self.input_mode == InputMode::Search {
+            self.search_field.cursor_pos = self.search_field.value.len();
+        }
+
         // hide dropdown if necessary (normally impossible but never have faith in users)
         match self.input_mode {
             InputMode::Normal | InputMode::Search => self.hide_dropdown(),
@@ -71,7 +888,20 @@ impl CodewarsCLI {
             _ => 0,
         };
 
-        let datas = match self.input_mode {
+        // the items can be sorted, so the field index isn't always the position in the dropdown
+        let datas = self.dropdown_datas();
+        let position = datas.iter().position(|(_, i)| *i == selected).unwrap_or(0);
+        self.field_dropdown = (true, StatefulList::with_items(datas, position));
+    }
+
+    pub fn hide_dropdown(&mut self) {
+        self.field_dropdown = (false, StatefulList::with_items(vec![], 0));
+        self.dropdown_filter = InputWidget::default();
+    }
+
+    /// all the items of the current field dropdown, with their index in the field const
+    fn dropdown_datas(&self) -> Vec<(String, usize)> {
+        let mut datas = match self.input_mode {
             InputMode::SortBy => Vec::from(SORT_BY),
             InputMode::Langage => Vec::from(LANGAGE),
             InputMode::Difficulty => Vec::from(DIFFICULTY),
@@ -83,278 +913,1647 @@ impl CodewarsCLI {
         .map(|(i, d)| (d.to_string(), i))
         .collect::<Vec<(String, usize)>>();
 
-        self.field_dropdown = (true, StatefulList::with_items(datas, selected));
+        if self.dropdown_sorted && self.is_dropdown_searchable() {
+            // "All", "My Languages" and "Select Tags" stay on top
+            let pinned = match self.input_mode {
+                InputMode::Langage => 2,
+                _ => 1,
+            };
+            datas[pinned..].sort_by_key(|(d, _)| d.to_lowercase());
+        }
+        return datas;
     }
 
-    pub fn hide_dropdown(&mut self) {
-        self.field_dropdown = (false, StatefulList::with_items(vec![], 0))
-    }
+    /// switch the languages/tags dropdown between the built-in and the alphabetical order, keeping the selected item
+    pub fn toggle_dropdown_sort(&mut self) {
+        if !self.is_dropdown_searchable() {
+            return;
+        }
+        let selected = self
+            .field_dropdown
+            .1
+            .items
+            .get(self.field_dropdown.1.state)
+            .map(|(_, i)| *i);
 
-    pub async fn submit_search(&mut self) {
-        // search by id
-        if self.search_field.value.len() == 24 {
-            if let Ok(data) = fetch_codewars_api(self.search_field.value.as_str()).await {
-                self.search_result = StatefulList::with_items(vec![(data, 0)], 0);
-                self.change_state(InputMode::KataList);
-                return;
+        self.dropdown_sorted = !self.dropdown_sorted;
+        self.filter_dropdown();
+        if let Some(selected) = selected {
+            if let Some(position) = self
+                .field_dropdown
+                .1
+                .items
+                .iter()
+                .position(|(_, i)| *i == selected)
+            {
+                self.field_dropdown.1.state = position;
             }
         }
+    }
 
-        // search by inputs
-        let url = self.build_url();
-        let resp = fetch_html(url).await;
+    /// only the long dropdowns have a search line
+    pub fn is_dropdown_searchable(&self) -> bool {
+        match self.input_mode {
+            InputMode::Langage | InputMode::Tags => true,
+            _ => false,
+        }
+    }
 
-        if let Ok(html_doc) = resp {
-            let document = Html::parse_document(html_doc.as_str());
+    /// keep only the dropdown items containing the search line value
+    pub fn filter_dropdown(&mut self) {
+        let usearch = self.dropdown_filter.value.to_lowercase();
+        let datas = self
+            .dropdown_datas()
+            .into_iter()
+            .filter(|(d, _)| d.to_lowercase().contains(usearch.trim()))
+            .collect::<Vec<(String, usize)>>();
 
-            let kata_selector = Selector::parse("main .list-item-kata").unwrap();
-            let tags_selector = Selector::parse(".keyword-tag").unwrap();
-            let languages_selector = Selector::parse("div div:nth-child(2) li a").unwrap();
-            let author_selector =
-                Selector::parse("a[data-tippy-content=\"This kata's Sensei\"]").unwrap();
-            let total_completed_selector = Selector::parse(
-                "span[data-tippy-content=\"Total times this kata has been completed\"]",
-            )
-            .unwrap();
-            let rank_selector = Selector::parse("span").unwrap(); // only the first item
+        self.field_dropdown.1 = StatefulList::with_items(datas, 0);
+    }
 
-            let mut katas: Vec<(KataAPI, usize)> = vec![];
-            for (i, element) in document.select(&kata_selector).enumerate() {
-                let mut kata = KataAPI::default();
+    pub fn set_message(&mut self, kind: MessageKind, message: String) {
+        self.message_expiry = match kind {
+            MessageKind::Success => Some(Instant::now() + MESSAGE_DURATION),
+            _ => None,
+        };
+        self.message = Some((kind, message));
+    }
 
-                kata.id = element.value().id().unwrap_or_default().to_string();
-                kata.url = format!("https://www.codewars.com/kata/{}", kata.id);
-                kata.name = element
-                    .value()
-                    .attr("data-title")
-                    .unwrap_or_default()
-                    .to_string();
+    /// clear the message once its time is over, called on each tick
+    pub fn expire_message(&mut self) {
+        if let Some(expiry) = self.message_expiry {
+            if Instant::now() >= expiry {
+                self.message = None;
+                self.message_expiry = None;
+            }
+        }
+    }
 
-                for tag_elem in element.select(&tags_selector) {
-                    kata.tags.push(tag_elem.text().to_string());
-                }
+    /// copy the markdown instruction of the selected kata, fallback to a temp file when there is no clipboard
+    pub async fn copy_kata_description(&mut self) {
+        let kata_idx = self.search_result.state;
+        let kata_id = match self.search_result.items.get(kata_idx) {
+            Some((kata, _)) => kata.id.to_owned(),
+            None => return,
+        };
 
-                for language_elem in element.select(&languages_selector) {
-                    kata.languages.push(
-                        language_elem
-                            .value()
-                            .attr("data-language")
-                            .unwrap_or_default()
-                            .to_string(),
+        // the search page doesn't give the instruction, only the api does
+        if self.search_result.items[kata_idx].0.description.len() <= 0 {
+            match self.source.kata(&kata_id).await {
+                Ok(data) => self.search_result.items[kata_idx].0.fill_details(&data),
+                Err(why) => {
+                    return self.set_message(
+                        MessageKind::Error,
+                        format!("Failed to fetch the kata instruction: {why}"),
                     )
                 }
+            }
+        }
 
-                kata.createdBy.username = match element.select(&author_selector).next() {
-                    Some(elem) => elem.text().to_string(),
-                    None => String::new(),
-                };
-
-                kata.totalCompleted = match element.select(&total_completed_selector).next() {
-                    Some(elem) => elem
-                        .text()
-                        .to_string()
-                        .replace(",", "")
-                        .parse::<usize>()
-                        .unwrap_or_default(),
-                    None => 0,
-                };
-
-                kata.rank.name = match element.select(&rank_selector).next() {
-                    Some(elem) => elem.text().to_string(),
-                    None => String::new(),
-                };
+        let description = self.search_result.items[kata_idx].0.description.to_owned();
+        let copied = copy_to_clipboard(
+            &mut self.clipboard,
+            &description,
+            &format!("codewars_{kata_id}.md"),
+        );
+        self.set_copy_message("Kata instruction", copied);
+    }
 
-                katas.push((kata, i));
-            }
+    /// copy the train command of the selected kata in the preferred language (a url by default, or a local runner command)
+    pub fn copy_train_command(&mut self) {
+        let kata = match self.search_result.items.get(self.search_result.state) {
+            Some((kata, _)) => kata.clone(),
+            None => return,
+        };
+        let language = self.preferred_language(&kata).unwrap_or_default();
 
-            if katas.len() <= 0 {
-                return; // TODO: error message to client
-            }
+        let template = self.settings.value().unwrap_or_default().train_command;
+        let command = kata.train_command(&template, &language);
+        let copied = copy_to_clipboard(
+            &mut self.clipboard,
+            &command,
+            &format!("codewars_{}_train.txt", kata.id),
+        );
+        self.set_copy_message(&format!("Train command ({command})"), copied);
+    }
 
-            self.search_result = StatefulList::with_items(katas, 0);
-            self.change_state(InputMode::KataList);
+    /// copy the names of the fetched katas, one per line with their rank
+    pub fn copy_kata_names(&mut self) {
+        if self.search_result.items.len() <= 0 {
+            return;
         }
+
+        let names = self
+            .search_result
+            .items
+            .iter()
+            .map(|(kata, _)| match kata.rank.name.len() {
+                0 => kata.name.to_owned(),
+                _ => format!("{} ({})", kata.name, kata.rank.name),
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+        let copied = copy_to_clipboard(&mut self.clipboard, &names, "codewars_katas.txt");
+        self.set_copy_message(
+            &format!("{} kata names", self.search_result.items.len()),
+            copied,
+        );
     }
 
-    pub fn run_preinstall(language: &str, path: &str) -> Result<String, String> {
-        match language {
-            "rust" => {
-                let cmd_res = Command::new("cargo").arg("init").current_dir(path).output();
-                match cmd_res {
-                    Ok(_) => Ok("src/".to_string()),
-                    Err(err) => Err(err.to_string()),
-                }
+    /// feedback of a copy action, `what` is the copied thing
+    fn set_copy_message(&mut self, what: &str, copied: Result<CopyOutcome, String>) {
+        match copied {
+            Ok(CopyOutcome::Clipboard) => {
+                self.set_message(MessageKind::Success, format!("{what} copied to clipboard"))
             }
-            _ => Err("this language doesn't exist".to_string()),
+            Ok(CopyOutcome::TempFile(path)) => self.set_message(
+                MessageKind::Info,
+                format!("No clipboard available, {what} written to {path}"),
+            ),
+            Err(why) => self.set_message(
+                MessageKind::Error,
+                format!("Failed to copy ({what}): {why}"),
+            ),
         }
     }
 
-    pub fn run_postinstall(mut editor: &str, path: &str) -> Result<(), String> {
-        if editor.len() <= 0 {
-            editor = "code"
-        }
+    /// replace the listed katas, the download modal is closed if its kata isn't in the list anymore (the queue one stays)
+    pub fn set_search_result(&mut self, katas: Vec<KataAPI>) {
+        let modal_kata_id = self.modal_kata_id();
+        let mut unique = vec![];
+        push_unique_katas(&mut unique, katas);
+        self.search_result = StatefulList::with_items(
+            unique
+                .into_iter()
+                .enumerate()
+                .map(|(i, kata)| (kata, i))
+                .collect::<Vec<(KataAPI, usize)>>(),
+            0,
+        );
 
-        match Command::new(editor).arg(path).output() {
-            Ok(_) => Ok(()),
-            Err(err) => Err(err.to_string()),
+        // the modal is closed when its kata isn't at the same index anymore
+        if !self.queue_modal
+            && self.download_modal.0 != DownloadModalInput::Disabled
+            && self.modal_kata_id() != modal_kata_id
+        {
+            self.download_modal = (DownloadModalInput::Disabled, 0);
+            self.download_langage = (false, StatefulList::with_items(vec![], 0));
+            self.download_progress = None;
         }
     }
 
-    pub fn autocomplete_path(&mut self) {
-        if self.download_path.cursor_pos != self.download_path.value.len() {
-            return; // if cursor at the end we don't want to autosuggest
-        }
-
-        let parts = self.download_path.value.split("/").collect::<Vec<&str>>();
-        let parent_dir = parts[0..parts.len() - 1].join("/");
-        if let Ok(child_dirs) = ls_dir(&parent_dir) {
-            let usearch = match parts.last() {
-                Some(data) => data.to_lowercase().trim().to_string(),
-                None => return,
-            };
+    /// id of the kata the download modal points to, if any
+    fn modal_kata_id(&self) -> Option<String> {
+        self.search_result
+            .items
+            .get(self.download_modal.1)
+            .map(|(kata, _)| kata.id.to_owned())
+    }
 
-            let match_dirs = child_dirs
-                .iter()
-                .filter(|d| d.to_lowercase().trim().starts_with(&usearch))
-                .map(|md| md.to_owned())
-                .collect::<Vec<String>>();
+    /// katas of the search inputs, up to `max_pages` pages (capped to MAX_PAGES) per search url from `first_page`.
+    /// Stops at the first page without new katas, the katas are deduped by id
+    async fn fetch_search_pages(
+        &mut self,
+        first_page: usize,
+        max_pages: usize,
+    ) -> Result<Vec<KataAPI>, FetchError> {
+        // one search per language of the local preset (if any)
+        let urls = match self.my_languages_preset() {
+            Some(languages) => languages.iter().map(|l| self.build_url(l)).collect(),
+            None => vec![self.build_url(&self.filter_language_slug().unwrap_or_default())],
+        };
 
-            self.download_path.suggestion = StatefulList::with_items(match_dirs, 0);
-        } else {
-            self.download_path.suggestion = StatefulList::with_items(vec![], 0);
-            // error message
+        let mut katas: Vec<KataAPI> = vec![];
+        for url in urls {
+            for page in first_page..first_page + max_pages.clamp(1, MAX_PAGES) {
+                let page_katas = self.source.search(format!("{url}&page={page}")).await?;
+                if push_unique_katas(&mut katas, page_katas) == 0 {
+                    break; // last page reached
+                }
+            }
         }
+
+        self.apply_client_filters(&mut katas);
+        Ok(katas)
     }
 
-    pub fn accept_path_suggestion(&mut self) {
-        if self.download_path.suggestion.items.len() <= 0 {
-            return;
+    /// filters the codewars search can't do, applied on the fetched katas
+    fn apply_client_filters(&self, katas: &mut Vec<KataAPI>) {
+        if let Some(author) = &self.author_filter {
+            katas.retain(|kata| &kata.createdBy.username == author);
+        }
+        if self.hide_beta {
+            katas.retain(|kata| !kata.beta);
         }
-
-        let parts = self.download_path.value.split("/").collect::<Vec<&str>>();
-        self.download_path.value = parts[0..parts.len() - 1].join("/")
-            + ("/".to_string()
-                + self.download_path.suggestion.items[self.download_path.suggestion.state]
-                    .as_str())
-            .as_str();
-        self.download_path.cursor_pos = self.download_path.value.len();
-        self.download_path.suggestion = StatefulList::with_items(vec![], 0)
     }
 
-    fn build_url(&self) -> String {
-        // query args
-        let query = format!("?q={}", encode(self.search_field.value.as_str()));
-
-        // sortby args
-        let sortby_value = match SORT_BY[self.sortby_field] {
-            "Oldest" => "published_at%20asc",
-            "Popularity" => "popularity%20desc",
-            "Positive Feedback" => "satisfaction_percent%20desc",
-            "Most Completed" => "total_completed%20desc",
-            "Least Completed" => "total_completed%20asc",
-            "Recently Published" => "published_at%20desc",
-            "Hardest" => "rank_id%20desc",
-            "Easiest" => "rank_id%20asc",
-            "Name" => "name%20asc",
-            "Low Satisfaction" => "satisfaction_percent%20asc",
-            _ => "",
-        }
-        .to_string();
-        let sortby = if sortby_value.len() <= 0 {
-            String::new()
-        } else {
-            format!("&order_by={sortby_value}")
-        };
+    pub async fn submit_search(&mut self) {
+        self.search_status = SearchStatus::Loading;
+        self.collection = None;
+        self.todo_view = false;
+        self.user_view = None;
+        self.loaded_pages = 0;
 
-        // language path
-        let language = match LANGAGE[self.langage_field] {
-            "All" => String::new(),
-            "C++" => "cpp".to_string(),
-            "Objective-C" => "objc".to_string(),
-            "C#" => "csharp".to_string(),
-            "F#" => "fsharp".to_string(),
-            "λ Calculus" => "lambdacalc".to_string(),
-            "RISC-V" => "riscv".to_string(),
-            l => l.to_lowercase().trim().replace(" ", "-"),
-        };
+        // search by id
+        let (query, _) = sanitize_query(&self.search_field.value, SEARCH_MAX_LEN);
+        if is_kata_id(&query) {
+            match self.source.kata(query.as_str()).await {
+                Ok(data) => {
+                    self.set_search_result(vec![data]);
+                    self.search_status = SearchStatus::Results;
+                    self.last_submitted_query = Some(self.search_signature());
+                    self.change_state(InputMode::KataList);
+                    return;
+                }
+                // not a kata id, search it as text
+                Err(FetchError::NotFound) => {}
+                Err(why) => {
+                    self.search_status = SearchStatus::Failed;
+                    self.set_message(MessageKind::Error, format!("Search failed: {why}"));
+                    self.retry = Some(RetryAction::Search);
+                    return;
+                }
+            }
+        }
 
-        // difficulty args
-        let difficulty = if self.difficulty_field == 0 {
-            String::new()
-        } else {
-            format!("&r%5B%5D=-{}", self.difficulty_field)
+        let max_pages = self.settings.value().unwrap_or_default().max_pages;
+        let katas = match self.fetch_search_pages(0, max_pages).await {
+            Ok(katas) => katas,
+            Err(FetchError::LoginRequired) => {
+                self.set_search_result(vec![]);
+                self.search_status = SearchStatus::LoginRequired;
+                self.set_message(
+                    MessageKind::Error,
+                    "This search requires login, set your codewars token (CODEWARS_TOKEN or api_token in the settings)"
+                        .to_string(),
+                );
+                return;
+            }
+            Err(why) => {
+                self.search_status = SearchStatus::Failed;
+                self.set_message(MessageKind::Error, format!("Search failed: {why}"));
+                self.retry = Some(RetryAction::Search);
+                return;
+            }
         };
 
-        // tags args
-        let tags = if self.tag_field == 0 {
-            String::new()
-        } else {
-            format!("&tags={}", encode(TAGS[self.tag_field]))
-        };
+        if katas.len() <= 0 {
+            self.set_search_result(vec![]);
+            self.search_status = SearchStatus::Empty;
+            return;
+        }
 
-        return format!("{CODEWARS_ENDPOINT}/{language}{query}{sortby}{difficulty}{tags}");
+        self.set_search_result(katas);
+        self.search_status = SearchStatus::Results;
+        self.loaded_pages = max_pages.clamp(1, MAX_PAGES);
+        self.last_submitted_query = Some(self.search_signature());
+        self.change_state(InputMode::KataList);
     }
-}
-
-pub struct Settings {
-    is_loaded: bool,
-    cache: SettingsDatas,
-}
 
-impl Settings {
-    fn load() -> Self {
-        Self {
-            is_loaded: false,
-            cache: SettingsDatas::default(),
+    /// append the next search page to the listed katas, the selection stays on the same kata
+    pub async fn load_more(&mut self) {
+        let searched = self.collection.is_none() && !self.todo_view && self.user_view.is_none();
+        if !searched || self.loaded_pages <= 0 || self.search_status != SearchStatus::Results {
+            return self.set_message(
+                MessageKind::Info,
+                "Only the results of a search can be extended".to_string(),
+            );
         }
-    }
 
-    fn get_file(read: bool, write: bool) -> Result<File, Box<dyn Error>> {
-        let uname = get_uname();
-        let path_str = format!("/home/{uname}/.cache/codewars_cli");
-        let path = Path::new(path_str.as_str());
+        self.set_message(MessageKind::Info, "Loading more katas...".to_string());
+        let katas = match self.fetch_search_pages(self.loaded_pages, 1).await {
+            Ok(katas) => katas,
+            Err(why) => {
+                return self.set_message(
+                    MessageKind::Error,
+                    format!("Failed to load more katas: {why}"),
+                )
+            }
+        };
+        self.loaded_pages += 1;
 
-        if let Err(why) = fs::create_dir_all(path) {
-            return Err(Box::new(why));
+        let added = self.append_search_result(katas);
+        if added <= 0 {
+            return self.set_message(MessageKind::Info, "No more katas".to_string());
         }
+        self.set_message(
+            MessageKind::Success,
+            format!(
+                "{added} more katas, {} listed",
+                self.search_result.items.len()
+            ),
+        );
+    }
 
-        let settings_file_path = format!("{path_str}/settings.json");
+    /// add the katas that aren't listed yet after the listed ones, returns how many were added
+    pub fn append_search_result(&mut self, katas: Vec<KataAPI>) -> usize {
+        let mut listed = std::mem::take(&mut self.search_result.items)
+            .into_iter()
+            .map(|(kata, _)| kata)
+            .collect::<Vec<KataAPI>>();
+        let added = push_unique_katas(&mut listed, katas);
+        self.search_result.items = listed
+            .into_iter()
+            .enumerate()
+            .map(|(i, kata)| (kata, i))
+            .collect();
+        added
+    }
 
-        let file = OpenOptions::new()
-            .create(true)
-            .read(read)
-            .write(write)
-            .open(Path::new(&settings_file_path))?;
+    /// the query and the filters of a search, two searches with the same signature give the same katas
+    fn search_signature(&self) -> String {
+        let (query, _) = sanitize_query(&self.search_field.value, SEARCH_MAX_LEN);
+        format!(
+            "{query}|{}|{}|{}|{}|{}|{}|{}",
+            self.sortby_field,
+            self.sort_reversed,
+            self.langage_field,
+            self.difficulty_field,
+            self.tag_field,
+            self.author_filter.to_owned().unwrap_or_default(),
+            self.hide_beta
+        )
+    }
 
-        return Ok(file);
+    /// Enter again on the search that is already listed, nothing to fetch
+    pub fn is_duplicate_search(&self) -> bool {
+        self.search_status == SearchStatus::Results
+            && self.collection.is_none()
+            && !self.todo_view
+            && self.user_view.is_none()
+            && self.last_submitted_query == Some(self.search_signature())
     }
 
-    pub fn value(&mut self) -> Result<SettingsDatas, Box<dyn Error>> {
-        if self.is_loaded {
-            return Ok(self.cache.clone());
+    /// language of the search filter if the kata has it, its first language otherwise
+    fn preferred_language(&self, kata: &KataAPI) -> Option<String> {
+        match self.filter_language_slug() {
+            Some(slug) if kata.languages.contains(&slug) => Some(slug),
+            _ => kata.languages.first().cloned(),
         }
-        return self.fetch_and_cache();
     }
 
-    pub fn fetch_and_cache(&mut self) -> Result<SettingsDatas, Box<dyn Error>> {
-        let mut file = Self::get_file(true, true)?;
-
-        let mut file_content = String::new();
-        file.read_to_string(&mut file_content)?;
+    /// copy the sample tests of the selected kata as a markdown code block (to ask for help on a chat/forum)
+    pub async fn copy_sample_tests(&mut self) {
+        let kata = match self.search_result.items.get(self.search_result.state) {
+            Some((kata, _)) => kata.clone(),
+            None => return,
+        };
+        let language = match self.preferred_language(&kata) {
+            Some(language) => language,
+            None => return,
+        };
 
-        let datas: SettingsDatas = serde_json::from_str(&file_content)?;
-        self.cache = datas.clone();
-        self.is_loaded = true;
+        let tests_lines =
+            match KataAPI::fetch_kata_download_info(&*self.source, &kata.id, Some(&language)).await
+            {
+                Ok((_, _, tests_lines)) => tests_lines,
+                Err(why) => {
+                    return self.set_message(
+                        MessageKind::Error,
+                        format!("Failed to fetch the sample tests: {why}"),
+                    )
+                }
+            };
 
-        return Ok(datas);
+        let code_block = format!("```{language}\n{}\n```\n", tests_lines.join("\n"));
+        let copied = copy_to_clipboard(
+            &mut self.clipboard,
+            &code_block,
+            &format!("codewars_{}_tests.md", kata.id),
+        );
+        self.set_copy_message(&format!("{language} sample tests"), copied);
     }
 
-    pub fn set(&mut self, datas: &SettingsDatas) -> Result<(), Box<dyn Error>> {
-        // Serialize data to a JSON string.
-        let data_buf = serde_json::to_string(&datas)?;
-
-        let mut file = Self::get_file(false, true)?;
-        writeln!(file, "{data_buf}")?;
+    /// write the sample code and tests of the selected kata in a single temp file, and open it in the editor
+    pub async fn open_scratch_file(&mut self) {
+        let kata = match self.search_result.items.get(self.search_result.state) {
+            Some((kata, _)) => kata.clone(),
+            None => return,
+        };
+        let language = match self.preferred_language(&kata) {
+            Some(language) => language,
+            None => return,
+        };
+
+        let (_, code_lines, tests_lines) =
+            match KataAPI::fetch_kata_download_info(&*self.source, &kata.id, Some(&language)).await
+            {
+                Ok(data) => data,
+                Err(why) => {
+                    return self.set_message(
+                        MessageKind::Error,
+                        format!("Failed to fetch the kata templates: {why}"),
+                    )
+                }
+            };
+
+        let comment = comment_prefix(&language);
+        let content = format!(
+            "{comment} {} ({})\n{}\n\n{comment} ---------- sample tests ----------\n{}\n",
+            kata.name,
+            kata.url,
+            code_lines.join("\n"),
+            tests_lines.join("\n")
+        );
+
+        let scratch_path = std::env::temp_dir().join(format!(
+            "codewars_{}_scratch{}",
+            trim_specials_chars(kata.name.to_lowercase().trim()),
+            language_to_extension(&language).unwrap_or_default()
+        ));
+        let scratch_path = scratch_path.to_string_lossy().to_string();
+        if let Err(why) = write_file(scratch_path.to_owned(), content) {
+            return self.set_message(MessageKind::Error, why);
+        }
+
+        let editor = match self.settings.value() {
+            Ok(SettingsDatas { editor_command, .. }) => editor_command,
+            Err(_) => String::new(),
+        };
+        match CodewarsCLI::run_postinstall(&editor, &scratch_path) {
+            Ok(_) => self.set_message(
+                MessageKind::Success,
+                format!("Scratch file written to {scratch_path}"),
+            ),
+            Err(why) => self.set_message(
+                MessageKind::Error,
+                format!("Scratch file written to {scratch_path} but the editor failed: {why}"),
+            ),
+        }
+    }
+
+    pub fn open_prompt(&mut self, kind: PromptKind, default_value: &str) {
+        let mut input = InputWidget::default();
+        input.push_str(default_value);
+        self.prompt = Some((kind, input));
+    }
+
+    /// Enter on the prompt
+    pub async fn submit_prompt(&mut self) {
+        let (kind, input) = match self.prompt.take() {
+            Some(prompt) => prompt,
+            None => return,
+        };
+
+        match kind {
+            PromptKind::Export => self.export_search_result(input.value.trim()).await,
+            PromptKind::Collection => self.open_collection(input.value.trim()).await,
+            PromptKind::ImportSolved => self.import_solved(input.value.trim()),
+            PromptKind::TodoNote => self.add_todo(input.value.trim()),
+            PromptKind::ImportFilters => self.import_filters(input.value.trim()).await,
+        }
+    }
+
+    /// list the katas of a collection, `collection` is its url or its id
+    pub async fn open_collection(&mut self, collection: &str) {
+        if collection.len() <= 0 {
+            return;
+        }
+        let url = if collection.starts_with("http") {
+            collection.to_string()
+        } else {
+            format!(
+                "{}/collections/{}",
+                codewars_base_url(),
+                collection.trim_matches('/')
+            )
+        };
+
+        self.search_status = SearchStatus::Loading;
+        match self.source.collection(url.to_owned()).await {
+            Ok(katas) => {
+                self.search_status = if katas.len() > 0 {
+                    SearchStatus::Results
+                } else {
+                    SearchStatus::Empty
+                };
+                self.set_search_result(katas);
+                self.collection = Some(url);
+                self.todo_view = false;
+                self.user_view = None;
+                self.change_state(InputMode::KataList);
+            }
+            Err(why) => {
+                self.search_status = SearchStatus::Failed;
+                self.set_message(
+                    MessageKind::Error,
+                    format!("Failed to load the collection: {why}"),
+                );
+                self.retry = Some(RetryAction::Collection(url));
+            }
+        }
+    }
+
+    /// list the katas authored or trained by the user account, it needs the api token and the username
+    pub async fn open_user_katas(&mut self, kind: UserKatas) {
+        let settings = self.settings.value().unwrap_or_default();
+        let username = settings.username.trim().to_string();
+        if codewars_token(&settings.api_token).is_none() || username.len() <= 0 {
+            return self.set_message(
+                MessageKind::Error,
+                "Set `username` and `api_token` (or CODEWARS_TOKEN) in the settings to list your katas"
+                    .to_string(),
+            );
+        }
+
+        self.search_status = SearchStatus::Loading;
+        match self.source.user_katas(&username, kind).await {
+            Ok(katas) => {
+                self.search_status = if katas.len() > 0 {
+                    SearchStatus::Results
+                } else {
+                    SearchStatus::Empty
+                };
+                self.set_search_result(katas);
+                self.collection = None;
+                self.todo_view = false;
+                self.user_view = Some(kind);
+                self.change_state(InputMode::KataList);
+            }
+            Err(why) => {
+                self.search_status = SearchStatus::Failed;
+                self.set_message(
+                    MessageKind::Error,
+                    format!("Failed to load your {} katas: {why}", kind.label()),
+                );
+                self.retry = Some(RetryAction::UserKatas(kind));
+            }
+        }
+    }
+
+    /// write the fetched katas as a markdown table, or as csv if the file ends with ".csv"
+    /// the search is fetched again with `export_max_pages` pages when it's more than the listed ones
+    pub async fn export_search_result(&mut self, path: &str) {
+        let settings = self.settings.value().unwrap_or_default();
+        let mut fetched = vec![];
+        let searched = self.collection.is_none() && !self.todo_view && self.user_view.is_none();
+        if searched
+            && self.search_status == SearchStatus::Results
+            && settings.export_max_pages > self.loaded_pages
+        {
+            fetched = match self.fetch_search_pages(0, settings.export_max_pages).await {
+                Ok(katas) => katas,
+                Err(why) => {
+                    return self.set_message(MessageKind::Error, format!("Export failed: {why}"))
+                }
+            };
+        }
+
+        let katas = if fetched.len() > 0 {
+            fetched
+        } else {
+            self.search_result
+                .items
+                .iter()
+                .map(|(kata, _)| kata.clone())
+                .collect::<Vec<KataAPI>>()
+        };
+        if katas.len() <= 0 {
+            return self.set_message(MessageKind::Error, "No katas to export".to_string());
+        }
+
+        let content = if path.to_lowercase().ends_with(".csv") {
+            katas_to_csv(&katas)
+        } else {
+            katas_to_markdown(&katas)
+        };
+        match write_file(path.to_string(), content) {
+            Ok(_) => self.set_message(
+                MessageKind::Success,
+                format!("{} katas exported to {path}", katas.len()),
+            ),
+            Err(why) => self.set_message(MessageKind::Error, why),
+        }
+    }
+
+    /// downloads saved in the data dir, oldest first
+    pub fn download_history() -> Vec<DownloadRecord> {
+        let path = match paths::data_file(HISTORY_FILE) {
+            Ok(path) => path,
+            Err(_) => return vec![],
+        };
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(_) => return vec![],
+        };
+        match serde_json::from_str(&content) {
+            Ok(history) => history,
+            // kept aside, the next download would write over it
+            Err(_) => {
+                if let Err(_) = backup_corrupt_file(&path) {}
+                vec![]
+            }
+        }
+    }
+
+    fn record_download(record: DownloadRecord) -> Result<(), String> {
+        let mut history = Self::download_history();
+        history.push(record);
+
+        let content = match serde_json::to_string_pretty(&history) {
+            Ok(content) => content,
+            Err(why) => return Err(why.to_string()),
+        };
+        write_file(
+            paths::data_file(HISTORY_FILE)?
+                .to_string_lossy()
+                .to_string(),
+            content,
+        )
+    }
+
+    /// warn when the kata of the modal was already downloaded in the selected language(s), or when its folder already exists
+    pub fn check_already_downloaded(&mut self) {
+        let (kata, languages, ..) = match modal_download(self) {
+            Some(download) => download,
+            None => return,
+        };
+
+        // in one of the selected languages
+        let last_download = Self::download_history().into_iter().rev().find(|record| {
+            record.kata_id == kata.id && record.languages.iter().any(|l| languages.contains(l))
+        });
+        let kata_dir = kata.kata_dir(&expand_path(&self.download_path.value));
+
+        self.download_warning = match last_download {
+            Some(record) => Some(format!(
+                "⚠ Already downloaded ({}) on {}",
+                record.languages.join(", "),
+                format_date(record.downloaded_at)
+            )),
+            None if Path::new(&kata_dir).exists() => Some(format!(
+                "⚠ {kata_dir} already exists, files will be overwritten"
+            )),
+            None => None,
+        };
+    }
+
+    /// list a random kata near the default difficulty, the same one for the whole day
+    pub async fn kata_of_the_day(&mut self) {
+        let today = format_date(unix_now());
+        let last_daily = match paths::data_file(DAILY_FILE) {
+            Ok(path) => fs::read_to_string(path)
+                .ok()
+                .and_then(|content| serde_json::from_str::<DailyKata>(&content).ok()),
+            Err(_) => None,
+        };
+
+        let kata = match last_daily {
+            Some(daily) if daily.date == today => self.source.kata(&daily.kata_id).await,
+            last_daily => {
+                let difficulty = self.settings.value().unwrap_or_default().default_difficulty;
+                let difficulty = if difficulty > 0 && difficulty < DIFFICULTY.len() {
+                    format!("&r%5B%5D=-{difficulty}")
+                } else {
+                    String::new()
+                };
+                let url = format!("{}/kata/search/?q={difficulty}", codewars_base_url());
+
+                match self.source.search(url).await {
+                    Ok(mut katas) => {
+                        // not twice in a row
+                        if let Some(daily) = last_daily {
+                            if katas.len() > 1 {
+                                katas.retain(|k| k.id != daily.kata_id);
+                            }
+                        }
+                        if katas.len() <= 0 {
+                            return self
+                                .set_message(MessageKind::Error, "No kata found".to_string());
+                        }
+                        let pick = rand_int(0, katas.len() as isize - 1) as usize;
+                        Ok(katas.swap_remove(pick))
+                    }
+                    Err(why) => Err(why),
+                }
+            }
+        };
+
+        let kata = match kata {
+            Ok(kata) => kata,
+            Err(why) => {
+                return self.set_message(
+                    MessageKind::Error,
+                    format!("Failed to fetch the kata of the day: {why}"),
+                )
+            }
+        };
+
+        let daily = DailyKata {
+            date: today,
+            kata_id: kata.id.to_owned(),
+        };
+        if let Ok(path) = paths::data_file(DAILY_FILE) {
+            if let Ok(content) = serde_json::to_string(&daily) {
+                if let Err(_) = write_file(path.to_string_lossy().to_string(), content) {}
+            }
+        }
+
+        let name = kata.name.to_owned();
+        self.collection = None;
+        self.todo_view = false;
+        self.user_view = None;
+        self.set_search_result(vec![kata]);
+        self.search_status = SearchStatus::Results;
+        self.change_state(InputMode::KataList);
+        self.set_message(
+            MessageKind::Info,
+            format!("Kata of the day: {name}, press D to download it"),
+        );
+    }
+
+    /// read the solved katas saved in the data dir
+    pub fn load_solved(&mut self) {
+        let path = match paths::data_file(SOLVED_FILE) {
+            Ok(path) => path,
+            Err(_) => return,
+        };
+        if let Ok(content) = fs::read_to_string(path) {
+            self.solved = parse_kata_ids(&content).into_iter().collect();
+        }
+    }
+
+    fn save_solved(&self) -> Result<(), String> {
+        let path = paths::data_file(SOLVED_FILE)?;
+        let mut ids = self.solved.iter().cloned().collect::<Vec<String>>();
+        ids.sort();
+        write_file(path.to_string_lossy().to_string(), ids.join("\n") + "\n")
+    }
+
+    pub fn load_todos(&mut self) {
+        let path = match paths::data_file(TODO_FILE) {
+            Ok(path) => path,
+            Err(_) => return,
+        };
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(_) => return,
+        };
+        match serde_json::from_str(&content) {
+            Ok(todos) => self.todos = todos,
+            // kept aside, the next to-do would write over it
+            Err(_) => match backup_corrupt_file(&path) {
+                Ok(backup) => self.set_message(
+                    MessageKind::Error,
+                    format!(
+                        "The to-dos file was corrupt, it's kept in {}",
+                        backup.display()
+                    ),
+                ),
+                Err(why) => self.set_message(
+                    MessageKind::Error,
+                    format!("The to-dos file is corrupt: {why}"),
+                ),
+            },
+        }
+    }
+
+    fn save_todos(&self) -> Result<(), String> {
+        let content = match serde_json::to_string_pretty(&self.todos) {
+            Ok(content) => content,
+            Err(why) => return Err(why.to_string()),
+        };
+        write_file(
+            paths::data_file(TODO_FILE)?.to_string_lossy().to_string(),
+            content,
+        )
+    }
+
+    /// note of the to-do of a kata, if it's bookmarked
+    pub fn todo_note(&self, kata_id: &str) -> Option<&str> {
+        self.todos
+            .iter()
+            .find(|todo| todo.kata_id == kata_id)
+            .map(|todo| todo.note.as_str())
+    }
+
+    /// ask for the note of the selected kata to-do, prefilled with the current one
+    pub fn open_todo_prompt(&mut self) {
+        let kata_id = match self.search_result.items.get(self.search_result.state) {
+            Some((kata, _)) => kata.id.to_owned(),
+            None => return,
+        };
+        let note = self.todo_note(&kata_id).unwrap_or_default().to_string();
+        self.open_prompt(PromptKind::TodoNote, &note);
+    }
+
+    /// bookmark the selected kata with a note, or update the note of its to-do
+    pub fn add_todo(&mut self, note: &str) {
+        let kata = match self.search_result.items.get(self.search_result.state) {
+            Some((kata, _)) => kata,
+            None => return,
+        };
+
+        match self.todos.iter_mut().find(|todo| todo.kata_id == kata.id) {
+            Some(todo) => todo.note = note.to_string(),
+            None => self.todos.push(TodoEntry {
+                kata_id: kata.id.to_owned(),
+                name: kata.name.to_owned(),
+                note: note.to_string(),
+                added_at: unix_now(),
+            }),
+        }
+        let name = kata.name.to_owned();
+        match self.save_todos() {
+            Ok(_) => self.set_message(MessageKind::Success, format!("{name} added to the to-dos")),
+            Err(why) => self.set_message(
+                MessageKind::Error,
+                format!("Failed to save the to-dos: {why}"),
+            ),
+        }
+    }
+
+    /// remove the selected kata from the to-dos, and from the list when the to-dos are listed
+    pub fn remove_todo(&mut self) {
+        let kata_id = match self.search_result.items.get(self.search_result.state) {
+            Some((kata, _)) => kata.id.to_owned(),
+            None => return,
+        };
+        if self.todo_note(&kata_id).is_none() {
+            return;
+        }
+
+        self.todos.retain(|todo| todo.kata_id != kata_id);
+        if let Err(why) = self.save_todos() {
+            return self.set_message(
+                MessageKind::Error,
+                format!("Failed to save the to-dos: {why}"),
+            );
+        }
+
+        if self.todo_view {
+            let position = self.search_result.state;
+            let katas = self
+                .search_result
+                .items
+                .drain(..)
+                .map(|(kata, _)| kata)
+                .filter(|kata| kata.id != kata_id)
+                .collect::<Vec<KataAPI>>();
+            self.set_search_result(katas);
+            self.search_result.state =
+                position.min(self.search_result.items.len().saturating_sub(1));
+            if self.search_result.items.len() <= 0 {
+                self.search_status = SearchStatus::Empty;
+            }
+        }
+        self.set_message(MessageKind::Success, "Removed from the to-dos".to_string());
+    }
+
+    /// list the to-dos, oldest first, in place of the search
+    pub async fn open_todos(&mut self) {
+        if self.todos.len() <= 0 {
+            return self.set_message(
+                MessageKind::Info,
+                "No to-do yet, press M on a kata to add it".to_string(),
+            );
+        }
+
+        self.search_status = SearchStatus::Loading;
+        let mut katas = vec![];
+        for todo in self.todos.to_owned() {
+            // the full kata for its languages, rank and tags; offline the saved name is enough to open it
+            let kata = match self.source.kata(&todo.kata_id).await {
+                Ok(kata) => kata,
+                Err(_) => KataAPI {
+                    id: todo.kata_id.to_owned(),
+                    name: todo.name.to_owned(),
+                    url: kata_url(&todo.kata_id, ""),
+                    ..KataAPI::default()
+                },
+            };
+            katas.push(kata);
+        }
+
+        self.set_search_result(katas);
+        self.search_status = SearchStatus::Results;
+        self.collection = None;
+        self.todo_view = true;
+        self.user_view = None;
+        self.change_state(InputMode::KataList);
+    }
+
+    /// mark (or unmark) the selected kata as solved
+    pub fn toggle_solved(&mut self) {
+        let kata_id = match self.search_result.items.get(self.search_result.state) {
+            Some((kata, _)) => kata.id.to_owned(),
+            None => return,
+        };
+
+        if !self.solved.remove(&kata_id) {
+            self.solved.insert(kata_id);
+        }
+        if let Err(why) = self.save_solved() {
+            self.set_message(
+                MessageKind::Error,
+                format!("Failed to save the solved katas: {why}"),
+            );
+        }
+    }
+
+    /// add the kata ids of a newline-delimited file to the solved ones
+    pub fn import_solved(&mut self, path: &str) {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(why) => {
+                return self
+                    .set_message(MessageKind::Error, format!("Failed to read {path}: {why}"))
+            }
+        };
+
+        let before = self.solved.len();
+        self.solved.extend(parse_kata_ids(&content));
+        match self.save_solved() {
+            Ok(_) => self.set_message(
+                MessageKind::Success,
+                format!("{} new solved katas imported", self.solved.len() - before),
+            ),
+            Err(why) => self.set_message(
+                MessageKind::Error,
+                format!("Failed to save the solved katas: {why}"),
+            ),
+        }
+    }
+
+    /// list the cache files with their size
+    pub fn open_cache_view(&mut self) {
+        match paths::cache_report() {
+            Ok(report) => self.cache_view = Some(report),
+            Err(why) => self.set_message(
+                MessageKind::Error,
+                format!("Failed to read the cache: {why}"),
+            ),
+        }
+    }
+
+    /// delete the cache files (the settings are kept), then refresh the view
+    pub fn clear_cache(&mut self) {
+        match paths::clear_cache() {
+            Ok(count) => {
+                self.set_message(MessageKind::Success, format!("{count} cache files deleted"));
+                self.open_cache_view();
+            }
+            Err(why) => self.set_message(
+                MessageKind::Error,
+                format!("Failed to clear the cache: {why}"),
+            ),
+        }
+    }
+
+    /// show the end of the log file, scrolled to its last line
+    pub fn open_log_view(&mut self) {
+        match read_log_tail(LOG_VIEW_MAX_LINES) {
+            Ok(lines) => self.log_view = Some((lines, 0)),
+            Err(why) => self.set_message(
+                MessageKind::Error,
+                format!("Failed to read the logs: {why}"),
+            ),
+        }
+    }
+
+    /// scroll the log view by `lines`, up towards the older ones
+    pub fn scroll_logs(&mut self, up: bool, lines: usize) {
+        if let Some((log, scroll)) = &mut self.log_view {
+            *scroll = match up {
+                true => (*scroll + lines).min(log.len().saturating_sub(1)),
+                false => scroll.saturating_sub(lines),
+            };
+        }
+    }
+
+    /// empty the log file, then refresh the view
+    pub fn clear_logs(&mut self) {
+        match clear_log() {
+            Ok(_) => {
+                self.set_message(MessageKind::Success, "Logs cleared".to_string());
+                self.open_log_view();
+            }
+            Err(why) => self.set_message(
+                MessageKind::Error,
+                format!("Failed to clear the logs: {why}"),
+            ),
+        }
+    }
+
+    /// copy the id of the selected kata (for the API, or to search it by id later)
+    pub fn copy_kata_id(&mut self) {
+        let kata_id = match self.search_result.items.get(self.search_result.state) {
+            Some((kata, _)) => kata.id.to_owned(),
+            None => return,
+        };
+
+        let copied = copy_to_clipboard(
+            &mut self.clipboard,
+            &kata_id,
+            &format!("codewars_{kata_id}_id.txt"),
+        );
+        self.set_copy_message(&format!("Kata id {kata_id}"), copied);
+    }
+
+    /// open the selected kata in the browser
+    pub fn open_selected_kata(&mut self) {
+        let kata = match self.search_result.items.get(self.search_result.state) {
+            Some((kata, _)) => kata,
+            None => return,
+        };
+
+        // the id can be missing when the scrapping failed
+        let url = kata.canonical_url();
+        if kata.id.trim().len() <= 0 || !is_valid_url(&url) {
+            return self.set_message(MessageKind::Error, "No URL for this kata".to_string());
+        }
+        if let Err(why) = open_url(&url) {
+            self.set_message(MessageKind::Error, format!("Couldn't open browser: {why}"));
+        }
+    }
+
+    /// open the folder of the last download of the selected kata in the file manager
+    pub fn reveal_downloaded_kata(&mut self) {
+        let kata_id = match self.search_result.items.get(self.search_result.state) {
+            Some((kata, _)) => kata.id.to_owned(),
+            None => return,
+        };
+        let record = Self::download_history()
+            .into_iter()
+            .rev()
+            .find(|record| record.kata_id == kata_id);
+
+        match record {
+            Some(record) => {
+                if let Err(why) = reveal_in_file_manager(&record.path) {
+                    self.set_message(
+                        MessageKind::Error,
+                        format!("Couldn't open the folder: {why}"),
+                    );
+                }
+            }
+            None => self.set_message(
+                MessageKind::Error,
+                "This kata hasn't been downloaded yet".to_string(),
+            ),
+        }
+    }
+
+    /// open the codewars profile of the selected kata author
+    pub fn open_author_profile(&mut self) {
+        let author = match self.search_result.items.get(self.search_result.state) {
+            Some((kata, _)) => kata.createdBy.clone(),
+            None => return,
+        };
+        let url = if author.url.len() > 0 {
+            author.url
+        } else if author.username.len() > 0 {
+            format!("{}/users/{}", codewars_base_url(), author.username)
+        } else {
+            return self.set_message(MessageKind::Error, "Unknown kata author".to_string());
+        };
+
+        if let Err(why) = open_url(&url) {
+            self.set_message(MessageKind::Error, format!("Couldn't open browser: {why}"));
+        }
+    }
+
+    /// only show the katas of the selected kata author (None to show every authors)
+    pub async fn set_author_filter(&mut self, follow_selected: bool) {
+        self.author_filter = if follow_selected {
+            match self.search_result.items.get(self.search_result.state) {
+                Some((kata, _)) if kata.createdBy.username.len() > 0 => {
+                    Some(kata.createdBy.username.to_owned())
+                }
+                _ => return,
+            }
+        } else {
+            None
+        };
+
+        self.submit_search().await;
+    }
+
+    /// hide (or show again) the beta katas of the search
+    pub async fn toggle_hide_beta(&mut self) {
+        self.hide_beta = !self.hide_beta;
+        self.submit_search().await;
+        self.set_message(
+            MessageKind::Info,
+            match self.hide_beta {
+                true => "Beta katas hidden, F to show them".to_string(),
+                false => "Beta katas shown".to_string(),
+            },
+        );
+    }
+
+    /// scaffold the kata folder, the user hook of the language is preferred over the built-ins.
+    /// Returns the folder (relative to `path`) where the sample files must be written
+    pub fn run_preinstall(
+        language: &str,
+        path: &str,
+        hooks: &HashMap<String, PreinstallHook>,
+        level: ScaffoldLevel,
+    ) -> Result<String, String> {
+        if level == ScaffoldLevel::None {
+            return Ok(String::new());
+        }
+        if let Some(hook) = hooks.get(language) {
+            let command = hook.command.replace("{path}", path);
+            let cmd_res = if cfg!(target_os = "windows") {
+                Command::new("cmd")
+                    .args(["/C", &command])
+                    .current_dir(path)
+                    .output()
+            } else {
+                Command::new("sh")
+                    .args(["-c", &command])
+                    .current_dir(path)
+                    .output()
+            };
+
+            let solution_dir = hook.solution_dir.trim_matches('/');
+            return match cmd_res {
+                Ok(out) if out.status.success() => Ok(if solution_dir.len() <= 0 {
+                    String::new()
+                } else {
+                    format!("{solution_dir}/")
+                }),
+                Ok(out) => Err(String::from_utf8_lossy(&out.stderr).trim().to_string()),
+                Err(err) => Err(err.to_string()),
+            };
+        }
+
+        match language {
+            "rust" => {
+                let mut cargo = Command::new("cargo");
+                cargo.arg("init").current_dir(path);
+                if level == ScaffoldLevel::Minimal {
+                    cargo.args(["--vcs", "none"]);
+                }
+                let cmd_res = cargo.output();
+                match cmd_res {
+                    Ok(_) => Ok("src/".to_string()),
+                    Err(err) => Err(err.to_string()),
+                }
+            }
+            _ => Err("this language doesn't exist".to_string()),
+        }
+    }
+
+    pub fn run_postinstall(mut editor: &str, path: &str) -> Result<(), String> {
+        if editor.len() <= 0 {
+            editor = "code"
+        }
+
+        match Command::new(editor).arg(path).output() {
+            Ok(_) => Ok(()),
+            Err(err) => Err(err.to_string()),
+        }
+    }
+
+    pub fn autocomplete_path(&mut self) {
+        if self.download_path.cursor_pos != self.download_path.value.len() {
+            return; // if cursor at the end we don't want to autosuggest
+        }
+
+        let parts = self.download_path.value.split("/").collect::<Vec<&str>>();
+        let parent_dir = expand_path(&parts[0..parts.len() - 1].join("/"));
+        if let Ok(child_dirs) = ls_dir(&parent_dir) {
+            let usearch = match parts.last() {
+                Some(data) => data.to_lowercase().trim().to_string(),
+                None => return,
+            };
+
+            let match_dirs = child_dirs
+                .iter()
+                .filter(|d| d.to_lowercase().trim().starts_with(&usearch))
+                .map(|md| (Path::new(&parent_dir).join(md).is_dir(), md.to_owned()))
+                .collect::<Vec<(bool, String)>>();
+
+            let limit = match self.settings.value() {
+                Ok(SettingsDatas {
+                    autocomplete_limit, ..
+                }) => autocomplete_limit,
+                Err(_) => SettingsDatas::default().autocomplete_limit,
+            };
+            self.download_path.suggestion =
+                StatefulList::with_items(sort_path_suggestions(match_dirs, limit), 0);
+        } else {
+            self.download_path.suggestion = StatefulList::with_items(vec![], 0);
+            // error message
+        }
+    }
+
+    pub fn accept_path_suggestion(&mut self) {
+        if self.download_path.suggestion.items.len() <= 0 {
+            return;
+        }
+
+        let parts = self.download_path.value.split("/").collect::<Vec<&str>>();
+        self.download_path.value = parts[0..parts.len() - 1].join("/")
+            + ("/".to_string()
+                + self.download_path.suggestion.items[self.download_path.suggestion.state]
+                    .as_str())
+            .as_str();
+        self.download_path.cursor_pos = self.download_path.value.len();
+        self.download_path.suggestion = StatefulList::with_items(vec![], 0)
+    }
+
+    /// codewars slug of the language filter (e.g: "C++" is "cpp"), None when no language is filtered
+    pub fn filter_language_slug(&self) -> Option<String> {
+        match LANGAGE[self.langage_field] {
+            "All" => None,
+            l => Some(language_slug(l)),
+        }
+    }
+
+    /// languages of the settings to search instead of codewars "My Languages" (which needs to be logged in),
+    /// None when "My Languages" isn't selected or no languages are set
+    pub fn my_languages_preset(&mut self) -> Option<Vec<String>> {
+        if LANGAGE[self.langage_field] != "My Languages" {
+            return None;
+        }
+        match self.settings.value() {
+            Ok(settings) if settings.my_languages.len() > 0 => Some(settings.my_languages),
+            _ => None,
+        }
+    }
+
+    /// open the download modal for the selected kata, or for the queued ones when `queue`
+    pub fn open_download_modal(&mut self, queue: bool) {
+        // the queued katas don't need to be listed anymore
+        if queue && self.download_queue.len() <= 0 {
+            return self.set_message(
+                MessageKind::Info,
+                "The queue is empty, add katas with Space".to_string(),
+            );
+        }
+        if !queue && self.search_result.items.len() <= 0 {
+            return;
+        }
+        self.queue_modal = queue;
+
+        // the path typed for another kata is kept only if the user asked for it
+        let remember_last_path = self.settings.value().unwrap_or_default().remember_last_path;
+        if !remember_last_path || self.download_path.value == String::new() {
+            self.download_path = InputWidget::default();
+            match self.settings.value() {
+                Ok(SettingsDatas { download_path, .. }) => {
+                    self.download_path.push_str(&download_path)
+                }
+                Err(_) => {
+                    let uname = get_uname();
+                    self.download_path
+                        .push_str(format!("/home/{uname}/").as_str());
+                }
+            }
+            self.autocomplete_path();
+        }
+        if self.editor_field.value == String::new() {
+            match self.settings.value() {
+                Ok(SettingsDatas { editor_command, .. }) => {
+                    self.editor_field.push_str(&editor_command)
+                }
+                Err(_) => self.editor_field.push_str("code"),
+            }
+        }
+
+        self.instruction_format = self.settings.value().unwrap_or_default().instruction_format;
+        self.set_download_languages();
+        self.download_modal = (DownloadModalInput::Langage, self.search_result.state);
+        if queue {
+            self.download_warning = None;
+        } else {
+            self.check_already_downloaded();
+        }
+    }
+
+    /// (kata id, language) of the solution template previewed in the download modal, the first language for all of them
+    pub fn template_preview_key(&self) -> Option<(String, String)> {
+        if self.queue_modal {
+            return None;
+        }
+        let kata = &self.search_result.items.get(self.download_modal.1)?.0;
+        let (language, _) = self
+            .download_langage
+            .1
+            .items
+            .get(self.download_langage.1.state)?;
+        let language = if language == ALL_LANGUAGES {
+            kata.languages.first()?
+        } else {
+            language
+        };
+        Some((kata.id.to_owned(), language.to_owned()))
+    }
+
+    /// add (or remove) the selected kata to the download queue
+    pub fn toggle_queued(&mut self) {
+        let kata = match self.search_result.items.get(self.search_result.state) {
+            Some((kata, _)) => kata,
+            None => return,
+        };
+
+        match self.download_queue.iter().position(|k| k.id == kata.id) {
+            Some(idx) => {
+                self.download_queue.remove(idx);
+            }
+            None => self.download_queue.push(kata.clone()),
+        }
+    }
+
+    /// fill the download modal languages with the selected kata ones,
+    /// the language filter of the search is preselected (and locked) when the kata has it,
+    /// else the first "My Languages" preset one it has
+    pub fn set_download_languages(&mut self) {
+        let mut languages = if self.queue_modal {
+            // every language of the queued katas, each kata is downloaded in the ones it has
+            let mut languages: Vec<String> = vec![];
+            for kata in &self.download_queue {
+                for language in &kata.languages {
+                    if !languages.contains(language) {
+                        languages.push(language.to_owned());
+                    }
+                }
+            }
+            languages
+        } else {
+            self.search_result.items[self.search_result.state]
+                .0
+                .languages
+                .to_owned()
+        };
+        if languages.len() > 1 {
+            languages.insert(0, ALL_LANGUAGES.to_string());
+        }
+
+        let filtered = match self.filter_language_slug() {
+            Some(slug) => languages.iter().position(|l| l == &slug),
+            None => None,
+        };
+        // with the "My Languages" preset, the first of the user languages that the kata has
+        let preset = match self.my_languages_preset() {
+            Some(preset) => preset
+                .iter()
+                .find_map(|p| languages.iter().position(|l| l == p)),
+            None => None,
+        };
+
+        self.download_langage = (
+            false,
+            StatefulList::with_items(
+                languages
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, s)| (s, i))
+                    .collect::<Vec<(String, usize)>>(),
+                filtered.or(preset).unwrap_or(0),
+            ),
+        );
+        self.download_langage_locked = filtered.is_some();
+    }
+
+    /// search url of the inputs, `language` is a codewars slug (empty for all languages)
+    fn build_url(&self, language: &str) -> String {
+        // query args
+        let (query, _) = sanitize_query(&self.search_field.value, SEARCH_MAX_LEN);
+        let query = format!("?q={}", encode(query.as_str()));
+
+        // sortby args, nothing for the default one (newest first)
+        let sortby = match self.sort_order() {
+            Some(_) if self.sortby_field == 0 && !self.sort_reversed => String::new(),
+            Some((column, true)) => format!("&order_by={column}%20desc"),
+            Some((column, false)) => format!("&order_by={column}%20asc"),
+            None => String::new(),
+        };
+
+        // difficulty args
+        let difficulty = if self.difficulty_field == 0 {
+            String::new()
+        } else {
+            format!("&r%5B%5D=-{}", self.difficulty_field)
+        };
+
+        // tags args
+        let tags = if self.tag_field == 0 {
+            String::new()
+        } else {
+            format!("&tags={}", encode(TAGS[self.tag_field]))
+        };
+
+        return format!(
+            "{}/kata/search/{language}{query}{sortby}{difficulty}{tags}",
+            codewars_base_url()
+        );
+    }
+
+    /// column and direction (true when descending) of the selected sort, with `sort_reversed` applied
+    pub fn sort_order(&self) -> Option<(&'static str, bool)> {
+        let (column, descending) = match SORT_BY[self.sortby_field] {
+            "Newest" => ("published_at", true),
+            "Oldest" => ("published_at", false),
+            "Popularity" => ("popularity", true),
+            "Positive Feedback" => ("satisfaction_percent", true),
+            "Most Completed" => ("total_completed", true),
+            "Least Completed" => ("total_completed", false),
+            "Recently Published" => ("published_at", true),
+            "Hardest" => ("rank_id", true),
+            "Easiest" => ("rank_id", false),
+            "Name" => ("name", false),
+            "Low Satisfaction" => ("satisfaction_percent", false),
+            _ => return None,
+        };
+        Some((column, descending != self.sort_reversed))
+    }
+
+    /// the query, the filters and the loaded pages as a string to share, the fields are saved by name to survive new items
+    pub fn filters_string(&self) -> String {
+        let (query, _) = sanitize_query(&self.search_field.value, SEARCH_MAX_LEN);
+        let mut args = vec![format!("q={}", encode(&query))];
+        let fields = [
+            ("sort", SORT_BY[self.sortby_field], self.sortby_field),
+            ("lang", LANGAGE[self.langage_field], self.langage_field),
+            (
+                "rank",
+                DIFFICULTY[self.difficulty_field],
+                self.difficulty_field,
+            ),
+            ("tag", TAGS[self.tag_field], self.tag_field),
+        ];
+        for (key, value, field) in fields {
+            if field != 0 {
+                args.push(format!("{key}={}", encode(value)));
+            }
+        }
+        if self.sort_reversed {
+            args.push("reversed=1".to_string());
+        }
+        if self.loaded_pages > 1 {
+            args.push(format!("pages={}", self.loaded_pages));
+        }
+        format!("{FILTERS_PREFIX}{}", args.join("&"))
+    }
+
+    /// set the query and the filters of a `filters_string`, nothing is changed if a part is invalid. Returns the pages to load
+    pub fn apply_filters_string(&mut self, filters: &str) -> Result<usize, String> {
+        let filters = filters.trim();
+        let filters = filters.strip_prefix(FILTERS_PREFIX).unwrap_or(filters);
+        let find = |items: &[&str], value: &str| {
+            items
+                .iter()
+                .position(|item| item.eq_ignore_ascii_case(value))
+                .ok_or(format!("unknown value \"{value}\""))
+        };
+
+        let (mut query, mut sortby, mut langage, mut difficulty, mut tag, mut pages) =
+            (String::new(), 0, 0, 0, 0, 1);
+        let mut reversed = false;
+        for arg in filters.split('&').filter(|arg| arg.len() > 0) {
+            let (key, value) = match arg.split_once('=') {
+                Some(pair) => pair,
+                None => return Err(format!("\"{arg}\" isn't a key=value pair")),
+            };
+            let value = match decode(value) {
+                Ok(value) => value.to_string(),
+                Err(_) => return Err(format!("\"{value}\" isn't url encoded")),
+            };
+            match key {
+                "q" => query = value,
+                "sort" => sortby = find(&SORT_BY, &value)?,
+                "lang" => langage = find(&LANGAGE, &value)?,
+                "rank" => difficulty = find(&DIFFICULTY, &value)?,
+                "tag" => tag = find(&TAGS, &value)?,
+                "reversed" => reversed = value == "1",
+                "pages" => match value.parse::<usize>() {
+                    Ok(count) => pages = count.clamp(1, MAX_PAGES),
+                    Err(_) => return Err(format!("\"{value}\" isn't a number of pages")),
+                },
+                _ => return Err(format!("unknown filter \"{key}\"")),
+            }
+        }
+
+        self.search_field = InputWidget::default();
+        self.search_field.push_str(&query);
+        self.sortby_field = sortby;
+        self.sort_reversed = reversed;
+        self.langage_field = langage;
+        self.difficulty_field = difficulty;
+        self.tag_field = tag;
+        Ok(pages)
+    }
+
+    /// copy the filters string of the current search
+    pub fn copy_filters(&mut self) {
+        let filters = self.filters_string();
+        let copied = copy_to_clipboard(&mut self.clipboard, &filters, "codewars_filters.txt");
+        self.set_copy_message("Filters", copied);
+    }
+
+    /// search with shared filters, loading as many pages as the sharer had
+    pub async fn import_filters(&mut self, filters: &str) {
+        let pages = match self.apply_filters_string(filters) {
+            Ok(pages) => pages,
+            Err(why) => {
+                return self.set_message(MessageKind::Error, format!("Invalid filters: {why}"))
+            }
+        };
+
+        self.submit_search().await;
+        self.refresh_field_hints();
+        while self.search_status == SearchStatus::Results
+            && self.loaded_pages > 0
+            && self.loaded_pages < pages
+        {
+            // a failed page isn't counted, stop there instead of fetching it again and again
+            let loaded = self.loaded_pages;
+            self.load_more().await;
+            if self.loaded_pages == loaded {
+                break;
+            }
+        }
+    }
+}
+
+pub struct Settings {
+    is_loaded: bool,
+    cache: SettingsDatas,
+    recovered: Option<PathBuf>, // backup of the corrupt file replaced by the defaults, until it's reported
+}
+
+impl Settings {
+    fn load() -> Self {
+        Self {
+            is_loaded: false,
+            cache: SettingsDatas::default(),
+            recovered: None,
+        }
+    }
+
+    /// settings of the file at `path`, the defaults when it's empty or missing.
+    /// A corrupt file is backed up and replaced by the defaults, the backup path is returned then
+    fn read_file(path: &Path) -> Result<(SettingsDatas, Option<PathBuf>), Box<dyn Error>> {
+        let file_content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(why) if why.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(why) => return Err(why.into()),
+        };
+
+        // freshly created file
+        if file_content.trim().len() <= 0 {
+            return Ok((SettingsDatas::default(), None));
+        }
+
+        match serde_json::from_str::<SettingsDatas>(&file_content) {
+            Ok(datas) => Ok((datas, None)),
+            Err(why) => {
+                log_print(format!("corrupt settings {}: {why}", path.display()));
+                let backup = backup_corrupt_file(path)?;
+                let defaults = SettingsDatas::default();
+                Self::write_file(path, &defaults)?;
+                Ok((defaults, Some(backup)))
+            }
+        }
+    }
+
+    /// replace the content of the file at `path`, a shorter content must not leave the end of the old one
+    fn write_file(path: &Path, datas: &SettingsDatas) -> Result<(), Box<dyn Error>> {
+        let data_buf = serde_json::to_string(&datas)?;
+        fs::write(path, format!("{data_buf}\n"))?;
+        Ok(())
+    }
+
+    /// backup of the corrupt settings replaced by the defaults since the last call, if any
+    pub fn take_recovered(&mut self) -> Option<PathBuf> {
+        self.recovered.take()
+    }
+
+    pub fn value(&mut self) -> Result<SettingsDatas, Box<dyn Error>> {
+        if self.is_loaded {
+            return Ok(self.cache.clone());
+        }
+        return self.fetch_and_cache();
+    }
+
+    pub fn fetch_and_cache(&mut self) -> Result<SettingsDatas, Box<dyn Error>> {
+        let (datas, backup) = Self::read_file(&paths::config_file()?)?;
+        if backup.is_some() {
+            self.recovered = backup;
+        }
+
+        let old_version = datas.version;
+        let datas = datas.migrate();
+        if old_version != datas.version {
+            // persist the migrated format so that it's only done once
+            self.set(&datas)?;
+        }
+
+        self.cache = datas.clone();
+        self.is_loaded = true;
+
+        return Ok(datas);
+    }
+
+    pub fn set(&mut self, datas: &SettingsDatas) -> Result<(), Box<dyn Error>> {
+        Self::write_file(&paths::config_file()?, datas)?;
+
+        self.cache = datas.clone();
+        self.is_loaded = true;
         Ok(())
     }
 }
@@ -380,6 +2579,8 @@ impl KataAPI {
                 url: String::new(),
             },
             publishedAt: String::new(),
+            approvedAt: String::new(),
+            beta: false,
             totalCompleted: 0,
             totalAttempts: 0,
             totalStars: 0,
@@ -387,97 +2588,928 @@ impl KataAPI {
         }
     }
 
-    pub async fn download(
+    /// `/kata/{id}/{slug}` once the slug is known (full kata fetched), `/kata/{id}` otherwise
+    pub fn canonical_url(&self) -> String {
+        kata_url(&self.id, &self.slug)
+    }
+
+    /// complete a scraped kata with the fields only the api gives
+    pub fn fill_details(&mut self, full: &KataAPI) {
+        if self.description.len() <= 0 {
+            self.description = full.description.to_owned();
+        }
+        if self.slug.len() <= 0 {
+            self.slug = full.slug.to_owned();
+        }
+        if self.category.len() <= 0 {
+            self.category = full.category.to_owned();
+        }
+    }
+
+    /// `template` with the placeholders of the kata replaced, see `train_command` of the settings
+    pub fn train_command(&self, template: &str, language: &str) -> String {
+        template
+            .replace("{id}", &self.id)
+            .replace("{slug}", &self.slug)
+            .replace("{language}", language)
+            .replace("{url}", &self.canonical_url())
+    }
+
+    /// folder in which the kata will be downloaded
+    pub fn kata_dir(&self, mut udownload_path: &str) -> String {
+        udownload_path = udownload_path.trim_end_matches("/");
+        format!(
+            "{udownload_path}/{}",
+            trim_specials_chars(self.name.to_lowercase().trim())
+        )
+    }
+
+    /// manifest `template` of the kata project, named after its slug
+    pub fn manifest(&self, template: &str) -> String {
+        let name = match self.slug.len() {
+            0 => trim_specials_chars(self.name.to_lowercase().trim()).replace("_", "-"),
+            _ => self.slug.to_owned(),
+        };
+        template.replace("{name}", &name)
+    }
+
+    /// README header `template` of the kata, see `readme_header` of the settings.
+    /// The placeholders between double quotes are escaped, for a valid YAML front matter
+    pub fn readme_header(&self, template: &str) -> String {
+        let escape = |value: &str| value.replace('\\', "\\\\").replace('"', "\\\"");
+        let values = [
+            ("{name}", self.name.to_owned()),
+            ("{rank}", self.rank.name.to_owned()),
+            ("{url}", self.canonical_url()),
+            ("{tags}", self.tags.join(", ")),
+            ("{author}", self.createdBy.username.to_owned()),
+        ];
+
+        let mut header = template.to_string();
+        for (placeholder, value) in values {
+            header = header
+                .replace(
+                    &format!("\"{placeholder}\""),
+                    &format!("\"{}\"", escape(&value)),
+                )
+                .replace(placeholder, &value);
+        }
+        header
+    }
+
+    /// README of the kata, with an index of the languages subfolders when downloading several languages at once
+    pub fn readme(&self, instruction: String, languages: &[String]) -> String {
+        if languages.len() <= 1 {
+            return instruction;
+        }
+
+        let index = languages
+            .iter()
+            .map(|l| format!("- [{l}]({l}/)"))
+            .collect::<Vec<String>>()
+            .join("\n");
+        format!(
+            "# {}\n\n## Languages\n\n{index}\n\n{instruction}",
+            self.name
+        )
+    }
+
+    /// write the (markdown) instruction in `download_path`, converted to `format`
+    pub fn write_instruction(
+        download_path: &str,
+        instruction: String,
+        format: InstructionFormat,
+    ) -> Result<(), String> {
+        let content = match format {
+            InstructionFormat::Markdown => instruction,
+            InstructionFormat::Html => markdown_to_html(&instruction),
+            InstructionFormat::Text => markdown_to_text(&instruction),
+        };
+        write_file(format!("{download_path}/{}", format.filename()), content)
+    }
+
+    /// path opened in the editor after downloading `languages` in `download_path`, see `editor_scope` of the settings
+    pub fn editor_target(
         &self,
+        download_path: &str,
+        languages: &[String],
+        settings: &SettingsDatas,
+    ) -> String {
+        match (settings.editor_scope, languages) {
+            (EditorScope::Solution, [language]) => {
+                let language_path = language_dir(download_path, language, false);
+                KataAPI::sample_files(
+                    language,
+                    &language_path,
+                    &settings.preinstall,
+                    settings.scaffold_level(language),
+                )
+                .0
+            }
+            _ => download_path.to_owned(),
+        }
+    }
+
+    /// solution and tests files of a language, where download_language writes them
+    pub fn sample_files(
         language: &str,
-        mut udownload_path: &str,
-        editor: &str,
-    ) -> Result<(), String> {
-        let (instruction, sample_code_lines, sample_tests_lines) =
-            match Self::fetch_kata_download_info(self.id.as_str(), Some(language)).await {
-                Ok(data) => data,
-                Err(err) => {
-                    return Err(err.to_string());
-                }
-            };
+        language_path: &str,
+        hooks: &HashMap<String, PreinstallHook>,
+        level: ScaffoldLevel,
+    ) -> (String, String) {
+        let solution_dir = match hooks.get(language) {
+            _ if level == ScaffoldLevel::None => String::new(),
+            Some(hook) => hook.solution_dir.trim_matches('/').to_string(),
+            None if language == "rust" => "src".to_string(), // cargo init
+            None => String::new(),
+        };
+        let solution_dir = match solution_dir.len() {
+            0 => String::new(),
+            _ => format!("{solution_dir}/"),
+        };
 
-        udownload_path = udownload_path.trim_end_matches("/");
-        let download_path = format!(
-            "{udownload_path}/{}",
-            trim_specials_chars(self.name.to_lowercase().trim())
-        );
+        let language_ext = language_to_extension(language).unwrap_or_default();
+        (
+            format!("{language_path}/{solution_dir}solution{language_ext}"),
+            format!("{language_path}/{solution_dir}tests{language_ext}"),
+        )
+    }
 
-        if let Err(why) = fs::create_dir_all(&download_path) {
-            return Err(why.to_string());
+    /// write the sample code & tests (`templates`) of one language in `download_path`
+    pub fn download_language(
+        &self,
+        templates: (Vec<String>, Vec<String>),
+        language: &str,
+        download_path: &str,
+        hooks: &HashMap<String, PreinstallHook>,
+        level: ScaffoldLevel,
+        allow_empty: bool,
+    ) -> Result<(), String> {
+        let (sample_code_lines, sample_tests_lines) = templates;
+        // the editors were found but nothing was read from them, e.g: a language shown differently
+        let is_empty = |lines: &[String]| lines.iter().all(|l| l.trim().len() <= 0);
+        if !allow_empty && is_empty(&sample_code_lines) && is_empty(&sample_tests_lines) {
+            return Err(format!(
+                "the {language} solution and tests were empty, set allow_empty_templates to download them anyway"
+            ));
         }
 
-        let preinstall = match CodewarsCLI::run_preinstall(language, download_path.as_str()) {
+        let preinstall = match CodewarsCLI::run_preinstall(language, download_path, hooks, level) {
             Ok(path) => path,
             Err(_) => String::new(),
         };
+        if let Err(why) = fs::create_dir_all(format!("{download_path}/{preinstall}")) {
+            return Err(why.to_string());
+        }
 
         let language_ext = language_to_extension(language).unwrap_or_default();
         let code_filename = format!("{download_path}/{}solution{}", preinstall, language_ext);
         let tests_filename = format!("{download_path}/{}tests{}", preinstall, language_ext);
-        let instruction_filename = format!("{download_path}/README.md");
 
         if let Err(why) = write_file(code_filename, sample_code_lines.join("\n")) {
             return Err(why.to_string());
         }
-        if let Err(why) = write_file(instruction_filename, instruction) {
-            return Err(why.to_string());
-        }
         if let Err(why) = write_file(tests_filename, sample_tests_lines.join("\n")) {
             return Err(why.to_string());
         }
-
-        if let Err(_) = CodewarsCLI::run_postinstall(editor, download_path.as_str()) {}
-
         Ok(())
     }
 
     // Fetch codewars sample code & instruction for puzzles
     pub async fn fetch_kata_download_info(
+        source: &dyn DataSource,
         kata_id: &str,
         langage: Option<&str>,
     ) -> Result<(String, Vec<String>, Vec<String>), Box<dyn Error>> {
-        let resp = match fetch_codewars_api(kata_id).await {
-            Ok(data) => data,
-            Err(why) => return Err(why.into()),
-        };
-        let instruction = resp.description; // instruction in markdown
+        let kata = source.kata(kata_id).await?;
+        let instruction = kata.description; // instruction in markdown
+        let (solution_field_lines, tests_field_lines) =
+            source.templates(kata_id, &kata.slug, langage).await?;
+        Ok((instruction, solution_field_lines, tests_field_lines))
+    }
+}
 
-        // get sample code
-        let browser = Browser::default()?;
-        let tab = browser.new_tab()?;
-        tab.navigate_to(&format!(
-            "https://www.codewars.com/kata/{}/train{}",
-            kata_id,
-            match langage {
-                Some(l) => "/".to_string() + l,
-                None => String::new(),
+/// submit the search, drawing the loading state first as the search blocks the UI
+async fn search<B: Backend>(terminal: &mut Terminal<B>, state: &mut CodewarsCLI) {
+    state.search_status = SearchStatus::Loading;
+    if let Err(_) = terminal.draw(|f| ui(f, state)) {}
+    state.submit_search().await;
+    state.refresh_field_hints();
+}
+
+/// next less constrained (tag, difficulty, language) fields and the name of the dropped filter,
+/// the tag goes first, then the difficulty, then the language. None when there is no filter left
+pub fn broadened_filters(
+    tag: usize,
+    difficulty: usize,
+    language: usize,
+) -> Option<((usize, usize, usize), &'static str)> {
+    if tag != 0 {
+        return Some(((0, difficulty, language), "tag"));
+    }
+    if difficulty != 0 {
+        return Some(((tag, 0, language), "difficulty"));
+    }
+    if language != 0 {
+        return Some(((tag, difficulty, 0), "language"));
+    }
+    return None;
+}
+
+/// drop the filters one by one until the search has results
+async fn broaden_search<B: Backend>(terminal: &mut Terminal<B>, state: &mut CodewarsCLI) {
+    let mut relaxed = vec![];
+    while state.search_status == SearchStatus::Empty {
+        let ((tag, difficulty, language), dropped) =
+            match broadened_filters(state.tag_field, state.difficulty_field, state.langage_field) {
+                Some(filters) => filters,
+                None => break,
+            };
+        state.tag_field = tag;
+        state.difficulty_field = difficulty;
+        state.langage_field = language;
+        relaxed.push(dropped);
+        search(terminal, state).await;
+    }
+
+    if relaxed.len() <= 0 {
+        state.set_message(
+            MessageKind::Info,
+            "No filter left to broaden, try another search".to_string(),
+        );
+        return;
+    }
+    match state.search_status {
+        SearchStatus::Results => state.set_message(
+            MessageKind::Info,
+            format!("Dropped the {} filter(s)", relaxed.join(", ")),
+        ),
+        SearchStatus::Empty => state.set_message(
+            MessageKind::Info,
+            format!(
+                "Dropped the {} filter(s), still no results",
+                relaxed.join(", ")
+            ),
+        ),
+        // the search failed, its error message is already set
+        _ => {}
+    }
+}
+
+/// replace the scraped languages of the modal kata by the api ones, fetched once per kata
+async fn refresh_kata_languages<B: Backend>(terminal: &mut Terminal<B>, state: &mut CodewarsCLI) {
+    if state.queue_modal || state.download_modal.0 == DownloadModalInput::Disabled {
+        return;
+    }
+    let kata_idx = state.download_modal.1;
+    let kata_id = match state.search_result.items.get(kata_idx) {
+        Some((kata, _)) => kata.id.to_owned(),
+        None => return,
+    };
+
+    let languages = match state.languages_cache.get(&kata_id) {
+        Some(languages) => languages.to_owned(),
+        None => {
+            state.languages_loading = true;
+            if let Err(_) = terminal.draw(|f| ui(f, state)) {}
+            let fetched = state.source.kata(&kata_id).await;
+            state.languages_loading = false;
+
+            if let Ok(data) = &fetched {
+                state.search_result.items[kata_idx].0.fill_details(data);
+            }
+            match fetched {
+                Ok(data) if data.languages.len() > 0 => {
+                    state
+                        .languages_cache
+                        .insert(kata_id, data.languages.to_owned());
+                    data.languages
+                }
+                // keep the scraped ones, the download will tell if the api is unreachable
+                _ => return,
             }
-        ))?;
+        }
+    };
 
-        let solution_field_elems = tab.wait_for_elements("#code div.CodeMirror-code > div > pre");
-        let solution_field_lines = match solution_field_elems {
-            Ok(lines) => lines
-                .iter()
-                .map(|line| line.get_inner_text().unwrap_or_default())
-                .collect::<Vec<String>>(),
-            Err(_) => return Err("failed to get the code sample".into()),
+    if state.search_result.items[kata_idx].0.languages != languages {
+        state.search_result.items[kata_idx].0.languages = languages;
+        state.set_download_languages();
+    }
+}
+
+/// show (fetching it the first time) or hide the solution template of the selected language in the download modal
+async fn toggle_template_preview<B: Backend>(terminal: &mut Terminal<B>, state: &mut CodewarsCLI) {
+    let key = state.template_preview_key();
+    let cached = match &key {
+        Some(key) => state.template_previews.contains_key(key),
+        None => true,
+    };
+    // the open preview of a language that isn't loaded yet loads it instead of closing
+    if state.template_preview && cached {
+        state.template_preview = false;
+        return;
+    }
+    state.template_preview = true;
+    let (kata_id, language) = match key {
+        Some(key) if !cached => key,
+        _ => return,
+    };
+    let slug = state.search_result.items[state.download_modal.1]
+        .0
+        .slug
+        .to_owned();
+
+    state.template_preview_loading = true;
+    if let Err(_) = terminal.draw(|f| ui(f, state)) {}
+    let fetched = state
+        .source
+        .templates(&kata_id, &slug, Some(&language))
+        .await;
+    state.template_preview_loading = false;
+
+    match fetched {
+        Ok((solution, _)) => {
+            state
+                .template_previews
+                .insert((kata_id, language), solution);
+        }
+        Err(why) => {
+            state.template_preview = false;
+            state.set_message(
+                MessageKind::Error,
+                format!("Couldn't fetch the {language} template: {why}"),
+            )
+        }
+    }
+}
+
+/// Esc or Ctrl+C pressed since the last check, the other keys are dropped while a long action runs.
+/// Ctrl+C also quits the app once the action is stopped
+fn cancel_requested(state: &mut CodewarsCLI) -> bool {
+    while let Ok(true) = event::poll(Duration::ZERO) {
+        if let Ok(Event::Key(key)) = event::read() {
+            if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+                state.quit_requested = true;
+                return true;
+            }
+            if key.code == KeyCode::Esc {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+/// fetch the full listed katas (instruction...) and save them for offline reading, Esc cancels it.
+/// One request at a time with a pause in between, to not get rate limited
+async fn save_offline<B: Backend>(terminal: &mut Terminal<B>, state: &mut CodewarsCLI) {
+    let mut saved = offline_katas();
+    let ids: Vec<String> = state
+        .search_result
+        .items
+        .iter()
+        .map(|(kata, _)| kata.id.to_owned())
+        .filter(|id| id.len() > 0 && !saved.contains_key(id))
+        .collect();
+    if state.search_result.items.len() <= 0 {
+        return state.set_message(MessageKind::Info, "No katas listed".to_string());
+    }
+    if ids.len() <= 0 {
+        return state.set_message(
+            MessageKind::Info,
+            "The listed katas are already saved offline".to_string(),
+        );
+    }
+
+    let (mut fetched, mut failed) = (0, 0);
+    let mut stopped: Option<&str> = None;
+    for (i, kata_id) in ids.iter().enumerate() {
+        if cancel_requested(state) {
+            stopped = Some("cancelled");
+            break;
+        }
+        state.set_message(
+            MessageKind::Info,
+            format!(
+                "Saving katas offline {}/{}... Esc to cancel",
+                i + 1,
+                ids.len()
+            ),
+        );
+        if let Err(_) = terminal.draw(|f| ui(f, state)) {}
+
+        let mut kata = state.source.kata(kata_id).await;
+        if let Err(FetchError::RateLimited) = kata {
+            tokio::time::sleep(OFFLINE_RATE_LIMIT_WAIT).await;
+            kata = state.source.kata(kata_id).await;
+        }
+        match kata {
+            Ok(kata) => {
+                if let Some((listed, _)) = state
+                    .search_result
+                    .items
+                    .iter_mut()
+                    .find(|(k, _)| &k.id == kata_id)
+                {
+                    listed.fill_details(&kata);
+                }
+                saved.insert(kata_id.to_owned(), kata);
+                fetched += 1;
+            }
+            Err(FetchError::RateLimited) => {
+                stopped = Some("rate limited by codewars");
+                break;
+            }
+            Err(_) => failed += 1,
+        }
+        tokio::time::sleep(OFFLINE_FETCH_DELAY).await;
+    }
+
+    if fetched > 0 {
+        if let Err(why) = save_offline_katas(&saved) {
+            return state.set_message(
+                MessageKind::Error,
+                format!("Failed to save the katas offline: {why}"),
+            );
+        }
+    }
+    let mut summary = format!("{fetched}/{} katas saved offline", ids.len());
+    if failed > 0 {
+        summary.push_str(&format!(", {failed} failed"));
+    }
+    match stopped {
+        Some(why) => state.set_message(MessageKind::Error, format!("{summary} ({why})")),
+        None if failed > 0 => state.set_message(MessageKind::Error, summary),
+        None => state.set_message(MessageKind::Success, summary),
+    }
+}
+
+/// folder of a language in the kata folder, with several languages each one has its own subfolder
+fn language_dir(download_path: &str, language: &str, several: bool) -> String {
+    if several {
+        format!("{download_path}/{language}")
+    } else {
+        download_path.to_owned()
+    }
+}
+
+/// download path to store after a download, the typed one replaces the default only if the user asked for it
+fn saved_download_path(settings: &SettingsDatas, typed: &str) -> String {
+    match settings.remember_last_path {
+        true => typed.to_owned(),
+        false => settings.download_path.to_owned(),
+    }
+}
+
+/// diff of the sample files already on disk that the download would overwrite, empty when nothing changes
+async fn changed_sample_files<B: Backend>(
+    terminal: &mut Terminal<B>,
+    state: &mut CodewarsCLI,
+    kata: &KataAPI,
+    languages: &[String],
+    udownload_path: &str,
+) -> Result<ChangedFiles, String> {
+    let settings = state.settings.value().unwrap_or_default();
+    let download_path = kata.kata_dir(&expand_path(udownload_path));
+    let mut changed = vec![];
+    for language in languages {
+        let language_path = language_dir(&download_path, language, languages.len() > 1);
+        let (solution_file, tests_file) = KataAPI::sample_files(
+            language,
+            &language_path,
+            &settings.preinstall,
+            settings.scaffold_level(language),
+        );
+        if !Path::new(&solution_file).exists() && !Path::new(&tests_file).exists() {
+            continue;
+        }
+
+        state.set_message(
+            MessageKind::Info,
+            format!("Comparing your {language} files with the fresh ones..."),
+        );
+        if let Err(_) = terminal.draw(|f| ui(f, state)) {}
+        let templates = state
+            .source
+            .templates(&kata.id, &kata.slug, Some(language))
+            .await
+            .map_err(|why| why.to_string());
+        state.message = None;
+        let (solution_lines, tests_lines) = templates?;
+        // the download writes the same ones
+        state.fetched_templates.insert(
+            (kata.id.to_owned(), language.to_owned()),
+            (solution_lines.clone(), tests_lines.clone()),
+        );
+
+        for (file, fresh) in [(solution_file, solution_lines), (tests_file, tests_lines)] {
+            let on_disk = match fs::read_to_string(&file) {
+                Ok(content) => content,
+                Err(_) => continue,
+            };
+            let diff = diff_lines(&on_disk, &fresh.join("\n"));
+            if diff.iter().any(|line| !matches!(line, DiffLine::Same(_))) {
+                changed.push((file, diff));
+            }
+        }
+    }
+    Ok(changed)
+}
+
+/// download the kata of the modal in the selected language(s), the progress is drawn between each language
+/// the kata is opened in `editor` if any
+async fn download_kata<B: Backend>(
+    terminal: &mut Terminal<B>,
+    state: &mut CodewarsCLI,
+    kata: &KataAPI,
+    languages: &[String],
+    udownload_path: &str,
+    editor: Option<&str>,
+) -> Result<(String, bool), String> {
+    let settings = state.settings.value().unwrap_or_default();
+    let (instruction, header) = match state.source.kata(kata.id.as_str()).await {
+        Ok(data) => (
+            data.description.to_owned(),
+            data.readme_header(&settings.readme_header),
+        ),
+        Err(err) => return Err(err.to_string()),
+    };
+
+    let download_path = kata.kata_dir(&expand_path(udownload_path));
+    for (i, language) in languages.iter().enumerate() {
+        state.download_progress = Some((i + 1, languages.len(), language.to_owned()));
+        if let Err(_) = terminal.draw(|f| ui(f, state)) {}
+
+        let language_path = language_dir(&download_path, language, languages.len() > 1);
+        if let Err(why) = fs::create_dir_all(&language_path) {
+            return Err(why.to_string());
+        }
+
+        // already fetched when the files on disk were compared with them
+        let templates = match state
+            .fetched_templates
+            .remove(&(kata.id.to_owned(), language.to_owned()))
+        {
+            Some(templates) => templates,
+            None => state
+                .source
+                .templates(&kata.id, &kata.slug, Some(language))
+                .await
+                .map_err(|why| why.to_string())?,
         };
+        kata.download_language(
+            templates,
+            language,
+            &language_path,
+            &settings.preinstall,
+            settings.scaffold_level(language),
+            settings.allow_empty_templates,
+        )?;
 
-        let tests_field_elems = tab.wait_for_elements("#fixture div.CodeMirror-code > div > pre");
-        let tests_field_lines = match tests_field_elems {
-            Ok(lines) => lines
-                .iter()
-                .map(|line| line.get_inner_text().unwrap_or_default())
-                .collect::<Vec<String>>(),
-            Err(_) => return Err("failed to get the code sample".into()),
+        if settings.write_manifest {
+            if let Some(manifest) = settings.manifests.get(language.as_str()) {
+                let manifest_path = format!("{language_path}/{}", manifest.file.trim_matches('/'));
+                if manifest.file.len() > 0 && !Path::new(&manifest_path).exists() {
+                    write_file(manifest_path, kata.manifest(&manifest.content))?;
+                }
+            }
+        }
+
+        // never overwrite the one of the user or of a scaffolding tool (cargo init)
+        let gitignore = format!("{language_path}/.gitignore");
+        if settings.write_gitignore && !Path::new(&gitignore).exists() {
+            if let Some(content) = gitignore_for(language) {
+                write_file(gitignore, content.to_string())?;
+            }
+        }
+    }
+
+    let mut readme = kata.readme(instruction, languages);
+    // the front matter would be rendered as text in the html and text formats
+    if state.instruction_format == InstructionFormat::Markdown && header.trim().len() > 0 {
+        readme = format!("{}\n\n{readme}", header.trim_end());
+    }
+    KataAPI::write_instruction(&download_path, readme, state.instruction_format)?;
+
+    let opened = match editor {
+        Some(editor) => {
+            let target = kata.editor_target(&download_path, languages, &settings);
+            CodewarsCLI::run_postinstall(editor, &target).is_ok()
+        }
+        None => false,
+    };
+    Ok((download_path, opened))
+}
+
+/// kata, languages, path and editor selected in the download modal
+fn modal_download(state: &CodewarsCLI) -> Option<(KataAPI, Vec<String>, String, String)> {
+    let kata = &state.search_result.items.get(state.download_modal.1)?.0;
+    let (language, _) = state
+        .download_langage
+        .1
+        .items
+        .get(state.download_langage.1.state)?;
+    let languages = if language == ALL_LANGUAGES {
+        kata.languages.to_owned()
+    } else {
+        vec![language.to_owned()]
+    };
+
+    let udownload_path = state.download_path.value.to_owned();
+    let editor = state.editor_field.value.to_owned();
+    Some((kata.clone(), languages, udownload_path, editor))
+}
+
+async fn submit_download<B: Backend>(terminal: &mut Terminal<B>, state: &mut CodewarsCLI) {
+    if state.queue_modal {
+        return download_queue(terminal, state).await;
+    }
+
+    let (kata, languages, udownload_path, editor) = match modal_download(state) {
+        Some(download) => download,
+        None => return state.download_modal = (DownloadModalInput::Disabled, 0),
+    };
+
+    // the same browser for the comparison and the download
+    state.fetched_templates.clear();
+    state.source.begin_batch();
+    // the user may have worked on the files of a previous download, show what would be lost first
+    match changed_sample_files(terminal, state, &kata, &languages, &udownload_path).await {
+        Ok(changed) if changed.len() > 0 => state.overwrite_diff = Some((changed, 0)),
+        Ok(_) => run_download(terminal, state, kata, languages, udownload_path, editor).await,
+        Err(why) => state.set_message(MessageKind::Error, format!("Download failed: {why}")),
+    }
+    state.source.end_batch();
+}
+
+/// the user accepted to overwrite the files shown in the diff
+async fn confirm_overwrite<B: Backend>(terminal: &mut Terminal<B>, state: &mut CodewarsCLI) {
+    state.overwrite_diff = None;
+    if let Some((kata, languages, udownload_path, editor)) = modal_download(state) {
+        run_download(terminal, state, kata, languages, udownload_path, editor).await;
+    }
+}
+
+async fn run_download<B: Backend>(
+    terminal: &mut Terminal<B>,
+    state: &mut CodewarsCLI,
+    kata_to_download: KataAPI,
+    languages: Vec<String>,
+    udownload_path: String,
+    editor: String,
+) {
+    state.source.begin_batch();
+    let download_result = download_kata(
+        terminal,
+        state,
+        &kata_to_download,
+        &languages,
+        &udownload_path,
+        Some(&editor),
+    )
+    .await;
+    state.source.end_batch();
+    state.download_progress = None;
+
+    let commit_message = format!(
+        "Add {} ({})",
+        kata_to_download.name, kata_to_download.rank.name
+    );
+    match download_result {
+        Ok((kata_path, opened)) => {
+            state.download_modal = (DownloadModalInput::Disabled, 0);
+            state.download_langage = (false, StatefulList::with_items(vec![], 0));
+            state.download_warning = None;
+
+            if let Err(_) = CodewarsCLI::record_download(DownloadRecord {
+                kata_id: kata_to_download.id.to_owned(),
+                languages: languages.to_owned(),
+                path: kata_path.to_owned(),
+                downloaded_at: unix_now(),
+            }) {}
+
+            // update store
+            let old_settings = state.settings.value().unwrap_or_default();
+            if let Err(_) = state.settings.set(&SettingsDatas {
+                editor_command: editor.to_owned(),
+                download_path: saved_download_path(&old_settings, &udownload_path),
+                instruction_format: state.instruction_format,
+                ..old_settings.clone()
+            }) {}
+
+            state.set_message(
+                MessageKind::Success,
+                format!(
+                    "Downloaded {} to {kata_path}{}",
+                    kata_to_download.name,
+                    if opened {
+                        format!(", opened with {editor}")
+                    } else {
+                        format!(" (failed to open it with {editor})")
+                    }
+                ),
+            );
+
+            if old_settings.git_autocommit && find_git_root(&kata_path).is_some() {
+                if let Err(why) = git_commit_kata(&kata_path, &commit_message) {
+                    state.set_message(
+                        MessageKind::Error,
+                        format!("Kata downloaded but not committed: {why}"),
+                    );
+                }
+            }
+        }
+        Err(why) => {
+            state.set_message(MessageKind::Error, format!("Download failed: {why}"));
+            state.retry = Some(RetryAction::Download {
+                kata: Box::new(kata_to_download),
+                languages,
+                udownload_path,
+                editor,
+            });
+        }
+    };
+}
+
+/// download every queued kata with the language/path/editor of the modal, then report the failures
+async fn download_queue<B: Backend>(terminal: &mut Terminal<B>, state: &mut CodewarsCLI) {
+    let language = match state
+        .download_langage
+        .1
+        .items
+        .get(state.download_langage.1.state)
+    {
+        Some((language, _)) => language.to_owned(),
+        None => return,
+    };
+    let udownload_path = state.download_path.value.to_owned();
+    let editor = state.editor_field.value.to_owned();
+
+    let queue = state.download_queue.clone();
+    let mut failures: Vec<String> = vec![];
+    state.source.begin_batch();
+    for kata in &queue {
+        let languages = if language == ALL_LANGUAGES {
+            kata.languages.to_owned()
+        } else if kata.languages.contains(&language) {
+            vec![language.to_owned()]
+        } else {
+            failures.push(format!("{} (no {language})", kata.name));
+            continue;
         };
 
-        Ok((instruction, solution_field_lines, tests_field_lines))
+        // one editor window per kata would flood the screen, see batch_open_editor
+        match download_kata(terminal, state, kata, &languages, &udownload_path, None).await {
+            Ok((kata_path, _)) => {
+                if let Err(_) = CodewarsCLI::record_download(DownloadRecord {
+                    kata_id: kata.id.to_owned(),
+                    languages,
+                    path: kata_path,
+                    downloaded_at: unix_now(),
+                }) {}
+                state.download_queue.retain(|k| k.id != kata.id);
+            }
+            Err(why) => failures.push(format!("{} ({why})", kata.name)),
+        }
+    }
+    state.source.end_batch();
+    state.download_progress = None;
+
+    let downloaded = queue.len() - failures.len();
+    let old_settings = state.settings.value().unwrap_or_default();
+    if downloaded > 0 && old_settings.batch_open_editor {
+        // the folder of all the katas, once
+        if let Err(_) = CodewarsCLI::run_postinstall(&editor, &expand_path(&udownload_path)) {}
+    }
+    if failures.len() <= 0 {
+        state.download_modal = (DownloadModalInput::Disabled, 0);
+        state.download_langage = (false, StatefulList::with_items(vec![], 0));
+        state.set_message(
+            MessageKind::Success,
+            format!("Downloaded {downloaded} katas to {udownload_path}"),
+        );
+    } else {
+        // the failed katas stay in the queue
+        state.set_message(
+            MessageKind::Error,
+            format!(
+                "Downloaded {downloaded}/{} katas, failed: {}",
+                queue.len(),
+                failures.join(", ")
+            ),
+        );
+    }
+
+    if let Err(_) = state.settings.set(&SettingsDatas {
+        editor_command: editor,
+        download_path: saved_download_path(&old_settings, &udownload_path),
+        instruction_format: state.instruction_format,
+        ..old_settings
+    }) {}
+}
+
+/// re-run the action that failed
+async fn retry<B: Backend>(
+    terminal: &mut Terminal<B>,
+    state: &mut CodewarsCLI,
+    action: RetryAction,
+) {
+    match action {
+        RetryAction::Search => search(terminal, state).await,
+        RetryAction::Collection(url) => state.open_collection(&url).await,
+        RetryAction::UserKatas(kind) => state.open_user_katas(kind).await,
+        RetryAction::Download {
+            kata,
+            languages,
+            udownload_path,
+            editor,
+        } => run_download(terminal, state, *kata, languages, udownload_path, editor).await,
+    }
+}
+
+/// the reported terminal size, raised to the smallest one the layout math handles
+fn terminal_size_or_min((width, height): (u16, u16)) -> (u16, u16) {
+    return (
+        width.max(TERMINAL_MIN_SIZE.0),
+        height.max(TERMINAL_MIN_SIZE.1),
+    );
+}
+
+/// run an action of the normal mode or of the list of katas, KeyAction::Quit is left to run_app
+async fn run_key_action<B: Backend>(
+    terminal: &mut Terminal<B>,
+    state: &mut CodewarsCLI,
+    action: KeyAction,
+) {
+    match action {
+        KeyAction::Quit => {}
+        KeyAction::Focus(mode) => state.change_state(mode),
+        KeyAction::OpenCollection => state.open_prompt(PromptKind::Collection, ""),
+        KeyAction::CacheView => state.open_cache_view(),
+        KeyAction::LogView => state.open_log_view(),
+        KeyAction::CopyFilters => state.copy_filters(),
+        KeyAction::ImportFilters => state.open_prompt(PromptKind::ImportFilters, ""),
+        KeyAction::ReloadSettings => state.reload_settings(),
+        KeyAction::KataOfTheDay => state.kata_of_the_day().await,
+        KeyAction::ImportSolved => state.open_prompt(PromptKind::ImportSolved, ""),
+        KeyAction::Todos => state.open_todos().await,
+        KeyAction::ToggleBeta => state.toggle_hide_beta().await,
+        KeyAction::PinFilters => state.save_default_filters(false),
+        KeyAction::ClearFilters => state.save_default_filters(true),
+        KeyAction::NarrowPanel => state.resize_split(-5),
+        KeyAction::WidenPanel => state.resize_split(5),
+        KeyAction::ToggleHelp => state.toggle_help(),
+        KeyAction::ListUserKatas(kind) => state.open_user_katas(kind).await,
+        KeyAction::NextKata => {
+            if state.search_result.items.len() > 0 {
+                state.search_result.step_next(state.wrap_navigation);
+            }
+        }
+        KeyAction::PreviousKata => {
+            if state.search_result.items.len() > 0 {
+                state.search_result.step_previous(state.wrap_navigation);
+            }
+        }
+        // across the rows when the katas are in columns
+        KeyAction::RowDown => state
+            .search_result
+            .step_down(state.grid_columns, state.wrap_navigation),
+        KeyAction::RowUp => state
+            .search_result
+            .step_up(state.grid_columns, state.wrap_navigation),
+        KeyAction::OpenKata => state.open_selected_kata(),
+        KeyAction::CopyDescription => state.copy_kata_description().await,
+        KeyAction::ScratchFile => state.open_scratch_file().await,
+        KeyAction::Export => {
+            let folder = match state.settings.value() {
+                Ok(SettingsDatas { download_path, .. }) if download_path.len() > 0 => download_path,
+                _ => format!("/home/{}", get_uname()),
+            };
+            state.open_prompt(
+                PromptKind::Export,
+                &format!("{}/katas.md", folder.trim_end_matches("/")),
+            )
+        }
+        KeyAction::CopyId => state.copy_kata_id(),
+        KeyAction::CopyNames => state.copy_kata_names(),
+        KeyAction::CopyTrainCommand => state.copy_train_command(),
+        KeyAction::CopySampleTests => state.copy_sample_tests().await,
+        KeyAction::ToggleSolved => state.toggle_solved(),
+        KeyAction::LoadMore => state.load_more().await,
+        KeyAction::ToggleIds => state.show_ids = !state.show_ids,
+        KeyAction::SaveOffline => save_offline(terminal, state).await,
+        KeyAction::RevealDownloaded => state.reveal_downloaded_kata(),
+        KeyAction::AddTodo => state.open_todo_prompt(),
+        KeyAction::RemoveTodo => state.remove_todo(),
+        KeyAction::AuthorProfile => state.open_author_profile(),
+        KeyAction::AuthorFilter => state.set_author_filter(true).await,
+        KeyAction::AllAuthors => state.set_author_filter(false).await,
+        KeyAction::Download => {
+            state.open_download_modal(false);
+            refresh_kata_languages(terminal, state).await;
+        }
+        KeyAction::Queue => state.toggle_queued(),
+        KeyAction::DownloadQueue => state.open_download_modal(true),
     }
 }
 
@@ -486,18 +3518,42 @@ pub async fn run_app<B: Backend>(
     state: &mut CodewarsCLI,
 ) -> Result<(), std::io::Error> {
     let mut first_loop = true;
-    state.terminal_size = size()?;
+    state.terminal_size = terminal_size_or_min(size().unwrap_or(TERMINAL_REF_SIZE));
+    let settings = state.settings.value().unwrap_or_default();
+    state.split_ratio = settings
+        .split_ratio
+        .clamp(SPLIT_RATIO_BOUNDS.0, SPLIT_RATIO_BOUNDS.1);
+    state.wrap_navigation = settings.wrap_navigation;
+    state.list_columns = settings.list_columns.max(1);
+    state.help_collapsed = settings.help_collapsed;
+    register_settings_token(&settings.api_token);
+    state.source.configure(&settings);
+    state.apply_default_filters(&settings);
+    state.load_solved();
+    state.load_todos();
+    state.warn_recovered_settings();
 
     loop {
+        if state.quit_requested {
+            return Ok(());
+        }
         terminal.draw(|f| ui(f, state))?;
 
         if first_loop {
-            state.submit_search().await;
+            search(terminal, state).await;
             first_loop = false
         }
 
+        if !event::poll(TICK_RATE)? {
+            state.expire_message();
+            continue;
+        }
+
         match event::read()? {
-            Event::Resize(w, h) => state.terminal_size = (w, h),
+            Event::Resize(w, h) => {
+                state.terminal_size = terminal_size_or_min((w, h));
+                state.banner = None;
+            }
             Event::Paste(data) => {
                 match state.download_modal.0 {
                     DownloadModalInput::Path => {
@@ -512,83 +3568,142 @@ pub async fn run_app<B: Backend>(
                     _ => {}
                 };
             }
+            Event::Mouse(_) if !state.mouse => {}
             Event::Mouse(mouse_ev) => {
+                match mouse_ev.kind {
+                    MouseEventKind::ScrollDown => state.scroll(true, mouse_ev.column),
+                    MouseEventKind::ScrollUp => state.scroll(false, mouse_ev.column),
+                    _ => {}
+                }
                 if mouse_ev.kind == MouseEventKind::Down(event::MouseButton::Left) {
-                    let delta_gap = (
-                        (state.terminal_size.0 as f32 - TERMINAL_REF_SIZE.0 as f32) * 0.3, // *0.3 = -70% (because this section have 30% of all screen, see ui())
-                        state.terminal_size.1 as i16 - TERMINAL_REF_SIZE.1 as i16,
-                    );
+                    if let Some(mode) = state.field_at(mouse_ev.column, mouse_ev.row) {
+                        state.change_state(mode)
+                    }
+                }
+            }
+            Event::Key(key) => {
+                let failed_action = state.retry.take();
+                state.message = None;
+                // raw mode swallows SIGINT, so Ctrl+C comes as a key
+                if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+                    return Ok(());
+                }
+                if key.code == KeyCode::Char('r') && key.modifiers.contains(KeyModifiers::CONTROL) {
+                    if let Some(action) = failed_action {
+                        retry(terminal, state, action).await;
+                    }
+                    continue;
+                }
+                if key.code == KeyCode::Char('b') && key.modifiers.contains(KeyModifiers::CONTROL) {
+                    if state.search_status == SearchStatus::Empty && state.collection.is_none() {
+                        broaden_search(terminal, state).await;
+                    }
+                    continue;
+                }
 
-                    if mouse_ev.column as i16 >= 2 && mouse_ev.column as f32 <= delta_gap.0 + 42.0 {
-                        if mouse_ev.row as i16 >= delta_gap.1 + 16
-                            && mouse_ev.row as i16 <= delta_gap.1 + 19
-                        {
-                            state.change_state(InputMode::Search)
-                        }
-                        if mouse_ev.row as i16 >= delta_gap.1 + 20
-                            && mouse_ev.row as i16 <= delta_gap.1 + 22
-                        {
-                            state.change_state(InputMode::SortBy)
+                if state.overwrite_diff.is_some() {
+                    match key.code {
+                        KeyCode::Char('y') | KeyCode::Char('Y') => {
+                            confirm_overwrite(terminal, state).await
                         }
-                        if mouse_ev.row as i16 >= delta_gap.1 + 23
-                            && mouse_ev.row as i16 <= delta_gap.1 + 25
-                        {
-                            state.change_state(InputMode::Langage)
+                        KeyCode::Down => state.scroll_diff(true),
+                        KeyCode::Up => state.scroll_diff(false),
+                        KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                            state.overwrite_diff = None;
+                            state.set_message(
+                                MessageKind::Info,
+                                "Download cancelled, your files are kept".to_string(),
+                            );
                         }
-                        if mouse_ev.row as i16 >= delta_gap.1 + 26
-                            && mouse_ev.row as i16 <= delta_gap.1 + 28
-                        {
-                            state.change_state(InputMode::Difficulty)
+                        _ => {}
+                    }
+                } else if state.cache_view.is_some() {
+                    match key.code {
+                        KeyCode::Char('X') | KeyCode::Char('x') => state.clear_cache(),
+                        KeyCode::Char('K') | KeyCode::Char('k') | KeyCode::Esc => {
+                            state.cache_view = None
                         }
-                        if mouse_ev.row as i16 >= delta_gap.1 + 29
-                            && mouse_ev.row as i16 <= delta_gap.1 + 32
-                        {
-                            state.change_state(InputMode::Tags)
+                        _ => {}
+                    }
+                } else if state.log_view.is_some() {
+                    match key.code {
+                        KeyCode::Up => state.scroll_logs(true, 1),
+                        KeyCode::Down => state.scroll_logs(false, 1),
+                        KeyCode::PageUp => state.scroll_logs(true, LOG_VIEW_PAGE),
+                        KeyCode::PageDown => state.scroll_logs(false, LOG_VIEW_PAGE),
+                        KeyCode::Char('X') | KeyCode::Char('x') => state.clear_logs(),
+                        KeyCode::Char('V') | KeyCode::Char('v') | KeyCode::Esc => {
+                            state.log_view = None
                         }
+                        _ => {}
                     }
-                }
-            }
-            Event::Key(key) => {
-                if state.field_dropdown.0 {
+                } else if let Some((_, input)) = &mut state.prompt {
                     match key.code {
-                        KeyCode::Up => state.field_dropdown.1.previous(),
-                        KeyCode::Down => state.field_dropdown.1.next(),
+                        KeyCode::Char(c) => input.push_char(c),
+                        KeyCode::Backspace => input.backspace(),
+                        KeyCode::Delete => input.del(),
+                        KeyCode::Left => input.move_cursor(CursorDirection::LEFT),
+                        KeyCode::Right => input.move_cursor(CursorDirection::RIGHT),
+                        KeyCode::Enter => state.submit_prompt().await,
+                        KeyCode::Esc => state.prompt = None,
+                        _ => {}
+                    }
+                } else if state.field_dropdown.0 {
+                    match key.code {
+                        KeyCode::Up => state.field_dropdown.1.step_previous(state.wrap_navigation),
+                        KeyCode::Down => state.field_dropdown.1.step_next(state.wrap_navigation),
                         KeyCode::Enter => {
-                            match state.input_mode {
-                                InputMode::SortBy => {
-                                    state.sortby_field = state.field_dropdown.1.state
+                            // the items can be filtered, so the selected position isn't always the field index
+                            let selected = match state
+                                .field_dropdown
+                                .1
+                                .items
+                                .get(state.field_dropdown.1.state)
+                            {
+                                Some((_, i)) => *i,
+                                None => {
+                                    state.hide_dropdown();
+                                    continue;
                                 }
-                                InputMode::Langage => {
-                                    state.langage_field = state.field_dropdown.1.state
-                                }
-                                InputMode::Difficulty => {
-                                    state.difficulty_field = state.field_dropdown.1.state
-                                }
-                                InputMode::Tags => state.tag_field = state.field_dropdown.1.state,
+                            };
+
+                            match state.input_mode {
+                                InputMode::SortBy => state.sortby_field = selected,
+                                InputMode::Langage => state.langage_field = selected,
+                                InputMode::Difficulty => state.difficulty_field = selected,
+                                InputMode::Tags => state.tag_field = selected,
                                 _ => {}
                             };
 
                             state.hide_dropdown();
-                            state.submit_search().await;
+                            search(terminal, state).await;
+                        }
+                        KeyCode::Char(c) if state.is_dropdown_searchable() => {
+                            state.dropdown_filter.push_char(c);
+                            state.filter_dropdown();
                         }
+                        KeyCode::Backspace if state.is_dropdown_searchable() => {
+                            state.dropdown_filter.backspace();
+                            state.filter_dropdown();
+                        }
+                        KeyCode::Tab => state.toggle_dropdown_sort(),
                         KeyCode::Esc => state.hide_dropdown(),
                         _ => {}
                     }
                 } else {
                     match state.input_mode {
-                        InputMode::Normal => match key.code {
-                            KeyCode::Char('q') => return Ok(()),
-                            KeyCode::Char('S') | KeyCode::Char('s') => state.submit_search().await,
-                            KeyCode::Char('L') | KeyCode::Char('l') => {
-                                state.change_state(InputMode::KataList)
-                            }
-                            KeyCode::Tab => state.change_state(InputMode::Search),
-                            _ => {}
+                        InputMode::Normal => match keymap_action(NORMAL_KEYMAP, key.code) {
+                            Some(KeyAction::Quit) => return Ok(()),
+                            Some(action) => run_key_action(terminal, state, action).await,
+                            None => {}
                         },
 
                         InputMode::Search => match key.code {
                             KeyCode::Char(c) => state.search_field.push_char(c),
-                            KeyCode::Enter => state.submit_search().await,
+                            KeyCode::Enter if state.is_duplicate_search() => {
+                                state.change_state(InputMode::KataList)
+                            }
+                            KeyCode::Enter => search(terminal, state).await,
                             KeyCode::Backspace => state.search_field.backspace(),
                             KeyCode::Delete => state.search_field.del(),
                             KeyCode::Left => state.search_field.move_cursor(CursorDirection::LEFT),
@@ -602,6 +3717,9 @@ pub async fn run_app<B: Backend>(
 
                         InputMode::SortBy => match key.code {
                             KeyCode::Enter => state.show_dropdown(),
+                            KeyCode::Char('r') | KeyCode::Char('R') => {
+                                state.sort_reversed = !state.sort_reversed
+                            }
                             KeyCode::Tab | KeyCode::Down => state.change_state(InputMode::Langage),
                             KeyCode::BackTab | KeyCode::Up => state.change_state(InputMode::Search),
                             KeyCode::Esc => state.change_state(InputMode::Normal),
@@ -638,80 +3756,27 @@ pub async fn run_app<B: Backend>(
                         },
 
                         InputMode::KataList => match state.download_modal.0 {
-                            DownloadModalInput::Disabled => match key.code {
-                                KeyCode::Tab | KeyCode::Down => {
-                                    if state.search_result.items.len() > 0 {
-                                        state.search_result.next();
-                                    }
-                                }
-                                KeyCode::BackTab | KeyCode::Up => {
-                                    if state.search_result.items.len() > 0 {
-                                        state.search_result.previous();
-                                    }
-                                }
-                                KeyCode::Enter => {
-                                    if state.search_result.items.len() > 0 {
-                                        if let Err(_) = open_url(
-                                            &state.search_result.items[state.search_result.state]
-                                                .0
-                                                .url,
-                                        ) {}
-                                    }
-                                }
-                                KeyCode::Char('D') | KeyCode::Char('d') => {
-                                    if state.download_path.value == String::new() {
-                                        match state.settings.value() {
-                                            Ok(SettingsDatas { download_path, .. }) => {
-                                                state.download_path.push_str(&download_path)
-                                            }
-                                            Err(_) => {
-                                                let uname = get_uname();
-                                                state
-                                                    .download_path
-                                                    .push_str(format!("/home/{uname}/").as_str());
-                                            }
-                                        }
-                                        state.autocomplete_path();
-                                    }
-                                    if state.editor_field.value == String::new() {
-                                        match state.settings.value() {
-                                            Ok(SettingsDatas { editor_command, .. }) => {
-                                                state.editor_field.push_str(&editor_command)
-                                            }
-                                            Err(_) => state.editor_field.push_str("code"),
-                                        }
-                                    }
-
-                                    state.download_langage = (
-                                        false,
-                                        StatefulList::with_items(
-                                            state.search_result.items[state.search_result.state]
-                                                .0
-                                                .languages
-                                                .iter()
-                                                .enumerate()
-                                                .map(|(i, s)| (s.to_owned(), i))
-                                                .collect::<Vec<(String, usize)>>(),
-                                            0,
-                                        ),
-                                    );
-                                    state.download_modal =
-                                        (DownloadModalInput::Langage, state.search_result.state);
+                            DownloadModalInput::Disabled => {
+                                if let Some(action) = keymap_action(KATA_LIST_KEYMAP, key.code) {
+                                    run_key_action(terminal, state, action).await
                                 }
-                                KeyCode::Esc => state.change_state(InputMode::Normal),
-                                _ => {}
-                            },
+                            }
                             DownloadModalInput::Langage => {
                                 if state.download_langage.0 {
                                     match key.code {
-                                        KeyCode::Tab | KeyCode::Down => {
-                                            state.download_langage.1.next()
-                                        }
-                                        KeyCode::BackTab | KeyCode::Up => {
-                                            state.download_langage.1.previous()
-                                        }
+                                        KeyCode::Tab | KeyCode::Down => state
+                                            .download_langage
+                                            .1
+                                            .step_next(state.wrap_navigation),
+                                        KeyCode::BackTab | KeyCode::Up => state
+                                            .download_langage
+                                            .1
+                                            .step_previous(state.wrap_navigation),
                                         KeyCode::Enter | KeyCode::Esc => {
-                                            state.download_langage.0 = false
+                                            state.download_langage.0 = false;
+                                            if !state.queue_modal {
+                                                state.check_already_downloaded();
+                                            }
                                         }
                                         _ => {}
                                     }
@@ -720,7 +3785,18 @@ pub async fn run_app<B: Backend>(
                                         KeyCode::Tab | KeyCode::Down => {
                                             state.download_modal.0 = DownloadModalInput::Path
                                         }
-                                        KeyCode::Enter => state.download_langage.0 = true,
+                                        KeyCode::Enter => {
+                                            if !state.download_langage_locked {
+                                                state.download_langage.0 = true
+                                            }
+                                        }
+                                        KeyCode::Char('u') | KeyCode::Char('U') => {
+                                            state.download_langage_locked =
+                                                !state.download_langage_locked
+                                        }
+                                        KeyCode::Char('p') | KeyCode::Char('P') => {
+                                            toggle_template_preview(terminal, state).await
+                                        }
                                         KeyCode::Esc => {
                                             state.download_modal.0 = DownloadModalInput::Disabled
                                         }
@@ -728,38 +3804,57 @@ pub async fn run_app<B: Backend>(
                                     }
                                 }
                             }
-                            DownloadModalInput::Path => match key.code {
-                                KeyCode::Char(c) => match c {
-                                    '>' => state.download_path.suggestion.next(),
-                                    '<' => state.download_path.suggestion.previous(),
-                                    ' ' => state.accept_path_suggestion(),
-                                    _ => {
+                            DownloadModalInput::Path => {
+                                let has_suggestions =
+                                    state.download_path.suggestion.items.len() > 0;
+                                let cursor_at_end = state.download_path.cursor_pos
+                                    == state.download_path.value.len();
+
+                                match key.code {
+                                    KeyCode::Char(c) => {
                                         state.download_path.push_char(c);
                                         state.autocomplete_path();
                                     }
-                                },
-                                KeyCode::Backspace => {
-                                    state.download_path.backspace();
-                                    state.autocomplete_path();
-                                }
-                                KeyCode::Delete => state.download_path.del(),
-                                KeyCode::Left => {
-                                    state.download_path.move_cursor(CursorDirection::LEFT)
-                                }
-                                KeyCode::Right => {
-                                    state.download_path.move_cursor(CursorDirection::RIGHT)
-                                }
-                                KeyCode::Tab | KeyCode::Down => {
-                                    state.download_modal.0 = DownloadModalInput::Editor
-                                }
-                                KeyCode::BackTab | KeyCode::Up => {
-                                    state.download_modal.0 = DownloadModalInput::Langage
-                                }
-                                KeyCode::Esc => {
-                                    state.download_modal.0 = DownloadModalInput::Disabled
+                                    KeyCode::Backspace => {
+                                        state.download_path.backspace();
+                                        state.autocomplete_path();
+                                    }
+                                    KeyCode::Delete => state.download_path.del(),
+                                    KeyCode::Left => {
+                                        state.download_path.move_cursor(CursorDirection::LEFT)
+                                    }
+                                    // like a shell: Tab/Shift+Tab cycle the suggestions, Right/End/Enter accept it
+                                    KeyCode::Right | KeyCode::End
+                                        if has_suggestions && cursor_at_end =>
+                                    {
+                                        state.accept_path_suggestion()
+                                    }
+                                    KeyCode::Enter => state.accept_path_suggestion(),
+                                    KeyCode::Right => {
+                                        state.download_path.move_cursor(CursorDirection::RIGHT)
+                                    }
+                                    KeyCode::End => {
+                                        state.download_path.cursor_pos =
+                                            state.download_path.value.len()
+                                    }
+                                    KeyCode::Tab if has_suggestions => {
+                                        state.download_path.suggestion.next()
+                                    }
+                                    KeyCode::BackTab if has_suggestions => {
+                                        state.download_path.suggestion.previous()
+                                    }
+                                    KeyCode::Tab | KeyCode::Down => {
+                                        state.download_modal.0 = DownloadModalInput::Editor
+                                    }
+                                    KeyCode::BackTab | KeyCode::Up => {
+                                        state.download_modal.0 = DownloadModalInput::Langage
+                                    }
+                                    KeyCode::Esc => {
+                                        state.download_modal.0 = DownloadModalInput::Disabled
+                                    }
+                                    _ => {}
                                 }
-                                _ => {}
-                            },
+                            }
                             DownloadModalInput::Editor => match key.code {
                                 KeyCode::Tab | KeyCode::Down => {
                                     state.download_modal.0 = DownloadModalInput::Submit
@@ -767,19 +3862,8 @@ pub async fn run_app<B: Backend>(
                                 KeyCode::BackTab | KeyCode::Up => {
                                     state.download_modal.0 = DownloadModalInput::Path
                                 }
-                                KeyCode::Char(c) => match c {
-                                    '>' => state.editor_field.suggestion.next(),
-                                    '<' => state.editor_field.suggestion.previous(),
-                                    ' ' => state.accept_path_suggestion(),
-                                    _ => {
-                                        state.editor_field.push_char(c);
-                                        state.autocomplete_path();
-                                    }
-                                },
-                                KeyCode::Backspace => {
-                                    state.editor_field.backspace();
-                                    state.autocomplete_path();
-                                }
+                                KeyCode::Char(c) => state.editor_field.push_char(c),
+                                KeyCode::Backspace => state.editor_field.backspace(),
                                 KeyCode::Delete => state.editor_field.del(),
                                 KeyCode::Left => {
                                     state.editor_field.move_cursor(CursorDirection::LEFT)
@@ -796,39 +3880,12 @@ pub async fn run_app<B: Backend>(
                                 KeyCode::BackTab | KeyCode::Up => {
                                     state.download_modal.0 = DownloadModalInput::Editor
                                 }
-                                KeyCode::Enter => {
-                                    let kata_to_download =
-                                        &state.search_result.items[state.download_modal.1].0;
-
-                                    let language = &state.download_langage.1.items
-                                        [state.download_langage.1.state]
-                                        .0;
-
-                                    let download_result = kata_to_download
-                                        .download(
-                                            language,
-                                            &state.download_path.value,
-                                            &state.editor_field.value,
-                                        )
-                                        .await;
-                                    match download_result {
-                                        Ok(_) => {
-                                            state.download_modal =
-                                                (DownloadModalInput::Disabled, 0);
-                                            state.download_langage =
-                                                (false, StatefulList::with_items(vec![], 0));
-
-                                            // update store
-                                            if let Err(_) = state.settings.set(&SettingsDatas {
-                                                editor_command: state.editor_field.value.to_owned(),
-                                                download_path: state.download_path.value.to_owned(),
-                                            }) {}
-                                            // TODO: ok message to user
-                                        }
-                                        Err(_) => {
-                                            // TODO: err message to user
-                                        }
-                                    };
+                                KeyCode::Enter => submit_download(terminal, state).await,
+                                KeyCode::Char('f') | KeyCode::Char('F') => {
+                                    state.instruction_format = state.instruction_format.next()
+                                }
+                                KeyCode::Char('p') | KeyCode::Char('P') => {
+                                    toggle_template_preview(terminal, state).await
                                 }
                                 KeyCode::Esc => {
                                     state.download_modal.0 = DownloadModalInput::Disabled
@@ -843,3 +3900,1045 @@ pub async fn run_app<B: Backend>(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datasource::Mock;
+    use tui::backend::TestBackend;
+
+    /// source of a codewars that can't be reached
+    struct Unreachable;
+
+    #[async_trait::async_trait(?Send)]
+    impl DataSource for Unreachable {
+        async fn search(&self, _url: String) -> Result<Vec<KataAPI>, FetchError> {
+            Err(FetchError::Timeout)
+        }
+
+        async fn collection(&self, _url: String) -> Result<Vec<KataAPI>, FetchError> {
+            Err(FetchError::Timeout)
+        }
+
+        async fn kata(&self, _kata_id: &str) -> Result<KataAPI, FetchError> {
+            Err(FetchError::Timeout)
+        }
+
+        async fn templates(
+            &self,
+            _kata_id: &str,
+            _slug: &str,
+            _langage: Option<&str>,
+        ) -> Result<(Vec<String>, Vec<String>), Box<dyn Error>> {
+            Err("unreachable".into())
+        }
+
+        async fn user_katas(
+            &self,
+            _username: &str,
+            _kind: UserKatas,
+        ) -> Result<Vec<KataAPI>, FetchError> {
+            Err(FetchError::Timeout)
+        }
+    }
+
+    /// app with the default settings (the user ones are left alone), in a terminal of the reference size
+    fn test_state() -> CodewarsCLI {
+        let mut state = CodewarsCLI::new(Box::new(Mock));
+        state.settings.is_loaded = true;
+        state.terminal_size = TERMINAL_REF_SIZE;
+        state
+    }
+
+    fn test_terminal() -> Terminal<TestBackend> {
+        Terminal::new(TestBackend::new(TERMINAL_REF_SIZE.0, TERMINAL_REF_SIZE.1)).unwrap()
+    }
+
+    /// symbols of the screen once the app is drawn
+    fn render(state: &mut CodewarsCLI) -> String {
+        let mut terminal = test_terminal();
+        terminal.draw(|f| ui(f, state)).unwrap();
+        terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|c| c.symbol.as_str())
+            .collect()
+    }
+
+    fn katas(count: usize) -> Vec<KataAPI> {
+        (0..count)
+            .map(|i| {
+                let mut kata = KataAPI::default();
+                kata.id = i.to_string();
+                kata.name = format!("kata {i}");
+                kata
+            })
+            .collect()
+    }
+
+    #[test]
+    fn sorted_dropdown_keeps_field_indexes() {
+        let mut state = test_state();
+        state.input_mode = InputMode::Tags;
+        state.tag_field = 2; // "Algebra"
+        state.dropdown_sorted = true;
+        state.show_dropdown();
+
+        let items = &state.field_dropdown.1.items;
+        assert_eq!(items[0], ("Select Tags".to_string(), 0));
+        assert_eq!(items[1], ("Algebra".to_string(), 2));
+        assert!(items.iter().all(|(d, i)| TAGS[*i] == d));
+        // the selected display item is the field one
+        assert_eq!(items[state.field_dropdown.1.state].1, 2);
+
+        // going back to the built-in order keeps the selection
+        state.field_dropdown.1.state = items.iter().position(|(d, _)| d == "Web3").unwrap();
+        state.toggle_dropdown_sort();
+        let items = &state.field_dropdown.1.items;
+        assert_eq!(
+            items[state.field_dropdown.1.state],
+            ("Web3".to_string(), TAGS.len() - 1)
+        );
+
+        state.input_mode = InputMode::Langage;
+        state.dropdown_sorted = true;
+        state.show_dropdown();
+        let items = &state.field_dropdown.1.items;
+        assert_eq!(items[0].0, "All");
+        assert_eq!(items[1].0, "My Languages");
+        assert_eq!(items[2].0, "Agda");
+        assert!(items.iter().all(|(d, i)| LANGAGE[*i] == d));
+    }
+
+    #[test]
+    fn filters_are_broadened_in_order() {
+        assert_eq!(broadened_filters(3, 5, 2), Some(((0, 5, 2), "tag")));
+        assert_eq!(broadened_filters(0, 5, 2), Some(((0, 0, 2), "difficulty")));
+        assert_eq!(broadened_filters(0, 0, 2), Some(((0, 0, 0), "language")));
+        assert_eq!(broadened_filters(0, 0, 0), None);
+    }
+
+    #[test]
+    fn minimal_kata_deserializes() {
+        let kata = serde_json::from_str::<KataAPI>(r#"{"id": "abc", "name": "Multiply"}"#)
+            .expect("a kata with only an id and a name must deserialize");
+        assert_eq!(kata.id, "abc");
+        assert_eq!(kata.name, "Multiply");
+        assert_eq!(kata.approvedAt, "");
+        assert_eq!(kata.slug, "");
+        assert_eq!(kata.category, "");
+        assert!(kata.languages.is_empty());
+        assert_eq!(kata.createdBy.username, "");
+        assert_eq!(kata.rank.name, "");
+    }
+
+    #[test]
+    fn shrinking_search_result_closes_download_modal() {
+        let mut state = test_state();
+        state.set_search_result(katas(3));
+        state.download_modal = (DownloadModalInput::Submit, 2);
+
+        state.set_search_result(katas(1));
+        assert!(state.download_modal.0 == DownloadModalInput::Disabled);
+        assert_eq!(state.download_modal.1, 0);
+
+        // same length, other katas
+        state.set_search_result(katas(3));
+        state.download_modal = (DownloadModalInput::Submit, 2);
+        let mut others = katas(3);
+        others
+            .iter_mut()
+            .for_each(|kata| kata.id.push_str("-other"));
+        state.set_search_result(others);
+        assert!(state.download_modal.0 == DownloadModalInput::Disabled);
+
+        // the same katas keep the modal open
+        state.download_modal = (DownloadModalInput::Submit, 2);
+        let same = state
+            .search_result
+            .items
+            .iter()
+            .map(|(kata, _)| kata.clone())
+            .collect();
+        state.set_search_result(same);
+        assert!(state.download_modal.0 == DownloadModalInput::Submit);
+    }
+
+    #[test]
+    fn stale_download_modal_index_doesnt_panic() {
+        let mut state = test_state();
+        state.set_search_result(katas(3));
+        state.download_modal = (DownloadModalInput::Submit, 2);
+        state.search_result.items.truncate(1); // replaced without going through set_search_result
+
+        render(&mut state);
+    }
+
+    #[test]
+    fn seeded_banner_renders_the_same() {
+        let render = || {
+            let mut state = test_state();
+            state.banner_colors = Box::new(crate::utils::SeededColors::new(7));
+            let mut terminal = test_terminal();
+            terminal.draw(|f| ui(f, &mut state)).unwrap();
+            terminal.backend().buffer().clone()
+        };
+        assert_eq!(render(), render());
+    }
+
+    #[test]
+    fn download_dropdown_fits_the_modal() {
+        assert_eq!(crate::ui::dropdown_height(1, 20), 3);
+        assert_eq!(crate::ui::dropdown_height(0, 20), 3);
+        assert_eq!(crate::ui::dropdown_height(40, 20), 20);
+        assert_eq!(crate::ui::dropdown_height(40, 0), 3);
+
+        for count in [1, 40] {
+            let mut state = test_state();
+            let mut kata = KataAPI::default();
+            kata.languages = (0..count).map(|i| format!("lang{i}")).collect();
+            state.set_search_result(vec![kata]);
+            state.open_download_modal(false);
+            state.download_langage.0 = true;
+            state.download_langage.1.state = state.download_langage.1.items.len() - 1;
+            render(&mut state);
+        }
+    }
+
+    #[test]
+    fn train_command_placeholders() {
+        let mut kata = KataAPI::default();
+        kata.id = "5277c8a221e209d3f6000b56".to_string();
+        kata.slug = "valid-braces".to_string();
+        assert_eq!(
+            kata.train_command(
+                "codewars train {id} --slug {slug} --lang {language}",
+                "rust"
+            ),
+            "codewars train 5277c8a221e209d3f6000b56 --slug valid-braces --lang rust"
+        );
+        assert_eq!(
+            kata.train_command("{url}/train/{language}", "python"),
+            format!("{}/train/python", kata.canonical_url())
+        );
+    }
+
+    #[test]
+    fn same_search_is_not_submitted_twice() {
+        let mut state = test_state();
+        state.search_field.push_str("braces");
+        state.set_search_result(katas(3));
+        state.search_status = SearchStatus::Results;
+        state.last_submitted_query = Some(state.search_signature());
+        assert!(state.is_duplicate_search());
+
+        state.search_field.push_char('!');
+        assert!(!state.is_duplicate_search());
+        state.search_field.backspace();
+        state.tag_field = 2;
+        assert!(!state.is_duplicate_search());
+    }
+
+    #[test]
+    fn beta_kata_with_null_rank_deserializes() {
+        let kata = serde_json::from_str::<KataAPI>(
+            r#"{"id": "abc", "name": "Beta", "approvedAt": null, "rank": {"id": null, "name": null, "color": null}}"#,
+        )
+        .expect("the nulls of a beta kata must fallback to the defaults");
+        assert_eq!(kata.approvedAt, "");
+        assert_eq!(kata.rank.name, "");
+        assert!(!kata.beta); // set from the rank once fetched, kept by the offline cache
+        let cached = serde_json::to_string(&KataAPI { beta: true, ..kata }).unwrap();
+        assert!(serde_json::from_str::<KataAPI>(&cached).unwrap().beta);
+
+        // the search page shows a "Beta" badge instead of the rank
+        let html = r#"<html><body><main>
+            <div class="list-item-kata" id="k1" data-title="Approved"><div><span>6 kyu</span></div></div>
+            <div class="list-item-kata" id="k2" data-title="Beta"><div><span>Beta</span></div></div>
+        </main></body></html>"#;
+        let mut katas = crate::datasource::parse_search_page(html);
+        assert!(!katas[0].beta);
+        assert_eq!(katas[0].rank.name, "6 kyu");
+        assert!(katas[1].beta);
+        assert_eq!(katas[1].rank.name, "");
+
+        let mut state = test_state();
+        state.apply_client_filters(&mut katas);
+        assert_eq!(katas.len(), 2);
+        state.hide_beta = true;
+        state.apply_client_filters(&mut katas);
+        assert_eq!(katas.len(), 1);
+        assert_eq!(katas[0].id, "k1");
+    }
+
+    #[test]
+    fn zero_terminal_size_doesnt_panic() {
+        assert_eq!(crate::ui::dropdown_items_in_view(&(0, 0), None), 1);
+        assert_eq!(crate::ui::dropdown_items_in_view(&(0, 0), Some(0)), 1);
+        assert_eq!(
+            crate::ui::dropdown_items_in_view(&TERMINAL_REF_SIZE, None),
+            26
+        );
+        assert_eq!(terminal_size_or_min((0, 0)), TERMINAL_MIN_SIZE);
+
+        let mut state = test_state();
+        state.terminal_size = (0, 0);
+        state.input_mode = InputMode::Langage;
+        state.show_dropdown();
+        state.field_dropdown.1.state = state.field_dropdown.1.items.len() - 1;
+
+        render(&mut state);
+    }
+
+    #[test]
+    fn default_manifest_is_named_after_the_kata() {
+        let settings = SettingsDatas::default();
+        let mut kata = KataAPI::default();
+        kata.name = "Valid Braces".to_string();
+
+        let manifest = &settings.manifests["javascript"];
+        assert_eq!(manifest.file, "package.json");
+        let package: serde_json::Value =
+            serde_json::from_str(&kata.manifest(&manifest.content)).unwrap();
+        assert_eq!(package["name"], "valid-braces");
+        assert_eq!(package["scripts"]["test"], "mocha tests.js");
+
+        kata.slug = "valid-braces-1".to_string();
+        let package: serde_json::Value =
+            serde_json::from_str(&kata.manifest(&settings.manifests["typescript"].content))
+                .unwrap();
+        assert_eq!(package["name"], "valid-braces-1");
+    }
+
+    #[test]
+    fn keymaps_bind_each_key_once() {
+        let focus = |c| keymap_action(NORMAL_KEYMAP, KeyCode::Char(c));
+        assert!(focus('t') == Some(KeyAction::Focus(InputMode::Tags)));
+        assert!(focus('d') == Some(KeyAction::Focus(InputMode::Difficulty)));
+        assert!(focus('S') == Some(KeyAction::Focus(InputMode::Search)));
+        assert!(focus('T') == Some(KeyAction::Todos));
+
+        for keymap in [NORMAL_KEYMAP, KATA_LIST_KEYMAP] {
+            let mut keys = keymap
+                .iter()
+                .flat_map(|(_, keys, ..)| keys.iter().map(|key| format!("{key:?}")))
+                .collect::<Vec<String>>();
+            let count = keys.len();
+            keys.sort();
+            keys.dedup();
+            assert_eq!(keys.len(), count);
+        }
+    }
+
+    #[test]
+    fn collapsed_help_renders() {
+        let mut state = test_state();
+        state.help_collapsed = true; // toggle_help would save it in the user settings
+
+        let screen = render(&mut state);
+        assert!(screen.contains("h: show the keys"));
+        assert!(!screen.contains("Quit app"));
+    }
+
+    #[test]
+    fn field_hints_point_at_the_empty_filter() {
+        let mut state = test_state(); // default settings, without my_languages
+        state.search_status = SearchStatus::Empty;
+        state.difficulty_field = 3;
+        state.langage_field = LANGAGE.iter().position(|l| *l == "My Languages").unwrap();
+        state.refresh_field_hints();
+
+        assert!(state.field_hint(&InputMode::Difficulty).is_some());
+        assert!(state.field_hint(&InputMode::Tags).is_none());
+        assert!(state
+            .field_hint(&InputMode::Langage)
+            .unwrap()
+            .contains("login"));
+
+        state.search_status = SearchStatus::Results;
+        state.refresh_field_hints();
+        assert!(state.field_hint(&InputMode::Difficulty).is_none());
+    }
+
+    #[test]
+    fn appended_katas_keep_the_selection() {
+        let mut state = test_state();
+        state.set_search_result(katas(3));
+        state.search_result.state = 2;
+
+        // "2" is already listed
+        let added = state.append_search_result(katas(5).into_iter().skip(2).collect());
+        assert_eq!(added, 2);
+        assert_eq!(state.search_result.state, 2);
+        let items = &state.search_result.items;
+        assert_eq!(items.len(), 5);
+        assert!(items
+            .iter()
+            .enumerate()
+            .all(|(i, (kata, idx))| *idx == i && kata.id == i.to_string()));
+        assert_eq!(state.append_search_result(katas(5)), 0);
+    }
+
+    #[test]
+    fn grid_navigation() {
+        // 0 1 2
+        // 3 4 5
+        // 6 7
+        let mut list = StatefulList::with_items((0..8).collect::<Vec<usize>>(), 1);
+        list.step_down(3, false);
+        assert_eq!(list.state, 4);
+        list.step_down(3, false);
+        assert_eq!(list.state, 7);
+        list.state = 5;
+        list.step_down(3, false); // nothing under it, the last kata
+        assert_eq!(list.state, 7);
+        list.step_down(3, false);
+        assert_eq!(list.state, 7);
+        list.step_down(3, true);
+        assert_eq!(list.state, 1);
+        list.step_up(3, true);
+        assert_eq!(list.state, 7);
+        list.state = 2;
+        list.step_up(3, true); // the last row has no third column
+        assert_eq!(list.state, 7);
+        list.state = 4;
+        list.step_up(3, false);
+        assert_eq!(list.state, 1);
+
+        // one column is the plain list
+        list.state = 7;
+        list.step_down(1, true);
+        assert_eq!(list.state, 0);
+
+        // scrolled by whole rows
+        list.state = 7;
+        assert_eq!(list.visible_grid_range(2, 3), 3..8);
+        list.state = 0;
+        assert_eq!(list.visible_grid_range(2, 3), 0..6);
+        assert_eq!(list.visible_grid_range(5, 3), 0..8);
+    }
+
+    #[test]
+    fn grid_renders() {
+        let mut state = test_state();
+        state.list_columns = 4;
+        state.set_search_result(katas(9));
+        state.search_result.state = 8;
+
+        // the list section of the reference size is too narrow for a second column
+        render(&mut state);
+        assert_eq!(state.grid_columns, 1);
+
+        let mut terminal = Terminal::new(TestBackend::new(220, TERMINAL_REF_SIZE.1)).unwrap();
+        terminal.draw(|f| ui(f, &mut state)).unwrap();
+        assert_eq!(state.grid_columns, 3);
+    }
+
+    #[test]
+    fn template_preview_renders_the_cached_template() {
+        let mut state = test_state();
+        let mut kata = KataAPI::default();
+        kata.id = "k1".to_string();
+        kata.languages = vec!["python".to_string(), "rust".to_string()];
+        state.set_search_result(vec![kata]);
+        state.open_download_modal(false);
+
+        // all the languages: the first one is previewed
+        let all = state
+            .download_langage
+            .1
+            .items
+            .iter()
+            .position(|(l, _)| l == ALL_LANGUAGES)
+            .unwrap();
+        state.download_langage.1.state = all;
+        assert_eq!(
+            state.template_preview_key(),
+            Some(("k1".to_string(), "python".to_string()))
+        );
+
+        state.template_previews.insert(
+            ("k1".to_string(), "python".to_string()),
+            vec!["def preview_me():".to_string()],
+        );
+        state.template_preview = true;
+        let rendered = render(&mut state);
+        assert!(rendered.contains("def preview_me():"));
+    }
+
+    #[test]
+    fn readme_header_placeholders() {
+        let mut kata = KataAPI::default();
+        kata.id = "5277c8a221e209d3f6000b56".to_string();
+        kata.slug = "valid-braces".to_string();
+        kata.name = "Valid Braces".to_string();
+        kata.rank.name = "6 kyu".to_string();
+        kata.tags = vec!["Algorithms".to_string(), "Strings".to_string()];
+        kata.createdBy.username = "xDranik".to_string();
+
+        assert_eq!(
+            kata.readme_header(&SettingsDatas::default().readme_header),
+            format!(
+                "---\nname: \"Valid Braces\"\nrank: \"6 kyu\"\nurl: {}\ntags: [Algorithms, Strings]\nauthor: \"xDranik\"\n---",
+                kata.canonical_url()
+            )
+        );
+        assert_eq!(
+            kata.readme_header("# {name} ({rank})"),
+            "# Valid Braces (6 kyu)"
+        );
+        // quoted values are escaped, the others are left as is
+        kata.name = r#"Say "hi" \ bye"#.to_string();
+        assert!(kata
+            .readme_header(&SettingsDatas::default().readme_header)
+            .contains(r#"name: "Say \"hi\" \\ bye""#));
+        assert_eq!(kata.readme_header("# {name}"), r#"# Say "hi" \ bye"#);
+    }
+
+    #[test]
+    fn overlapping_pages_are_deduped() {
+        let mut state = test_state();
+        // the same kata twice on a page, then pages overlapping on two katas
+        let mut first = katas(4);
+        first.push(first[1].clone());
+        state.set_search_result(first);
+        assert_eq!(state.append_search_result(katas(6)), 2);
+
+        let ids: Vec<&str> = state
+            .search_result
+            .items
+            .iter()
+            .map(|(kata, _)| kata.id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["0", "1", "2", "3", "4", "5"]);
+        assert!(state
+            .search_result
+            .items
+            .iter()
+            .enumerate()
+            .all(|(i, (_, idx))| i == *idx));
+
+        let mut pages = katas(3);
+        assert_eq!(push_unique_katas(&mut pages, katas(5)), 2);
+        assert_eq!(pages.len(), 5);
+    }
+
+    #[test]
+    fn editor_target_follows_the_scope() {
+        let kata = KataAPI::default();
+        let one = vec!["rust".to_string()];
+        let two = vec!["rust".to_string(), "python".to_string()];
+        let mut settings = SettingsDatas::default();
+        settings.preinstall.insert(
+            "python".to_string(),
+            PreinstallHook {
+                command: String::new(),
+                solution_dir: "/src/".to_string(),
+            },
+        );
+        let target = |languages: &[String], scope| {
+            let settings = SettingsDatas {
+                editor_scope: scope,
+                ..settings.clone()
+            };
+            kata.editor_target("/katas/kata", languages, &settings)
+        };
+
+        assert_eq!(target(&one, EditorScope::Folder), "/katas/kata");
+        assert_eq!(
+            target(&one, EditorScope::Solution),
+            "/katas/kata/src/solution.rs"
+        );
+        assert_eq!(
+            target(&["python".to_string()], EditorScope::Solution),
+            "/katas/kata/src/solution.py"
+        );
+        // no single solution file
+        assert_eq!(target(&two, EditorScope::Solution), "/katas/kata");
+    }
+
+    #[test]
+    fn log_view_scrolls_from_the_last_line() {
+        let mut state = test_state();
+        let lines: Vec<String> = (0..200).map(|i| format!("log line {i}")).collect();
+        state.log_view = Some((lines, 0));
+
+        let rendered = render(&mut state);
+        assert!(rendered.contains("log line 199"));
+        assert!(!rendered.contains("log line 0 "));
+
+        state.scroll_logs(true, 50);
+        let rendered = render(&mut state);
+        assert!(rendered.contains("log line 149"));
+        assert!(!rendered.contains("log line 150"));
+
+        // can't scroll past the first line, nor below the last one
+        state.scroll_logs(true, 1000);
+        assert_eq!(state.log_view.as_ref().unwrap().1, 199);
+        state.scroll_logs(false, 1000);
+        assert_eq!(state.log_view.as_ref().unwrap().1, 0);
+    }
+
+    #[test]
+    fn empty_templates_fail_the_download() {
+        let dir = std::env::temp_dir().join(format!("codewars_cli_empty_{}", std::process::id()));
+        let dir = dir.to_string_lossy().to_string();
+        let kata = KataAPI::default();
+        let hooks = HashMap::new();
+        // the code editors are found but empty
+        let templates = (vec![String::new()], vec![]);
+
+        let err = kata
+            .download_language(
+                templates.clone(),
+                "python",
+                &dir,
+                &hooks,
+                ScaffoldLevel::Full,
+                false,
+            )
+            .unwrap_err();
+        assert!(err.contains("empty"));
+        assert!(!Path::new(&format!("{dir}/solution.py")).exists());
+
+        kata.download_language(templates, "python", &dir, &hooks, ScaffoldLevel::Full, true)
+            .unwrap();
+        assert!(Path::new(&format!("{dir}/solution.py")).exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn filters_string_round_trip() {
+        let mut state = test_state();
+        state.search_field.push_str("valid braces & co");
+        state.sortby_field = 2;
+        state.langage_field = LANGAGE.iter().position(|l| *l == "C++").unwrap();
+        state.difficulty_field = 6;
+        state.tag_field = 0;
+        state.loaded_pages = 3;
+        let filters = state.filters_string();
+        assert!(filters.starts_with(FILTERS_PREFIX));
+        assert!(!filters.contains("tag="));
+
+        let mut imported = test_state();
+        imported.tag_field = 4;
+        assert_eq!(imported.apply_filters_string(&filters), Ok(3));
+        assert_eq!(imported.search_field.value, "valid braces & co");
+        assert_eq!(imported.sortby_field, 2);
+        assert_eq!(imported.langage_field, state.langage_field);
+        assert_eq!(imported.difficulty_field, 6);
+        assert_eq!(imported.tag_field, 0);
+
+        // an invalid part leaves the fields alone
+        assert!(imported
+            .apply_filters_string("cw-filters:q=x&tag=Not%20A%20Tag")
+            .is_err());
+        assert_eq!(imported.search_field.value, "valid braces & co");
+        assert!(imported.apply_filters_string("q=x&pages=two").is_err());
+    }
+
+    #[test]
+    fn reversed_sort_flips_the_direction() {
+        let mut state = test_state();
+        let order = |state: &CodewarsCLI| {
+            let url = state.build_url("");
+            url.split("&order_by=").nth(1).map(|o| o.to_string())
+        };
+        assert_eq!(order(&state), None); // newest, the codewars default
+        state.sort_reversed = true;
+        assert_eq!(order(&state), Some("published_at%20asc".to_string()));
+
+        state.sortby_field = SORT_BY.iter().position(|s| *s == "Most Completed").unwrap();
+        assert_eq!(state.sort_order(), Some(("total_completed", false)));
+        assert_eq!(order(&state), Some("total_completed%20asc".to_string()));
+        state.sort_reversed = false;
+        assert_eq!(order(&state), Some("total_completed%20desc".to_string()));
+    }
+
+    #[test]
+    fn corrupt_settings_are_backed_up() {
+        let dir =
+            std::env::temp_dir().join(format!("codewars_cli_settings_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("settings.json");
+
+        // cut while written
+        let corrupt = "{\"editor_command\": \"vim\", \"download_pa";
+        fs::write(&path, corrupt).unwrap();
+        let (datas, backup) = Settings::read_file(&path).unwrap();
+        let backup = backup.unwrap();
+        assert_eq!(
+            datas.editor_command,
+            SettingsDatas::default().editor_command
+        );
+        assert_eq!(backup, dir.join("settings.json.bak"));
+        assert_eq!(fs::read_to_string(&backup).unwrap(), corrupt);
+        // the defaults were written in place of the corrupt file
+        assert!(Settings::read_file(&path).unwrap().1.is_none());
+
+        // a shorter content replaces the whole file
+        let mut datas = SettingsDatas::default();
+        datas.editor_command = "a-very-long-editor-command --with --flags".to_string();
+        Settings::write_file(&path, &datas).unwrap();
+        datas.editor_command = "vi".to_string();
+        Settings::write_file(&path, &datas).unwrap();
+        let (datas, backup) = Settings::read_file(&path).unwrap();
+        assert!(backup.is_none());
+        assert_eq!(datas.editor_command, "vi");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn scaffold_level_moves_the_sample_files() {
+        let mut settings = SettingsDatas::default();
+        settings.preinstall.insert(
+            "python".to_string(),
+            PreinstallHook {
+                command: "poetry new --src {path}".to_string(),
+                solution_dir: "src/".to_string(),
+            },
+        );
+        let solution = |settings: &SettingsDatas, language: &str| {
+            KataAPI::sample_files(
+                language,
+                "/kata",
+                &settings.preinstall,
+                settings.scaffold_level(language),
+            )
+            .0
+        };
+
+        // cargo init and the preinstall command by default
+        assert_eq!(solution(&settings, "rust"), "/kata/src/solution.rs");
+        assert_eq!(solution(&settings, "python"), "/kata/src/solution.py");
+
+        settings
+            .scaffold
+            .insert("rust".to_string(), ScaffoldLevel::Minimal);
+        assert_eq!(solution(&settings, "rust"), "/kata/src/solution.rs");
+
+        settings
+            .scaffold
+            .insert("rust".to_string(), ScaffoldLevel::None);
+        settings
+            .scaffold
+            .insert("python".to_string(), ScaffoldLevel::None);
+        assert_eq!(solution(&settings, "rust"), "/kata/solution.rs");
+        assert_eq!(solution(&settings, "python"), "/kata/solution.py");
+        assert_eq!(
+            CodewarsCLI::run_preinstall("rust", "/kata", &settings.preinstall, ScaffoldLevel::None),
+            Ok(String::new())
+        );
+    }
+
+    #[test]
+    fn banner_keeps_its_colors_between_draws() {
+        let mut state = test_state();
+        let mut terminal = test_terminal();
+        terminal.draw(|f| ui(f, &mut state)).unwrap();
+        let first = terminal.backend().buffer().clone();
+        terminal.draw(|f| ui(f, &mut state)).unwrap();
+        assert_eq!(&first, terminal.backend().buffer());
+    }
+
+    #[test]
+    fn queue_modal_doesnt_need_the_search_result() {
+        let mut state = test_state();
+        state.download_queue = katas(2);
+
+        state.open_download_modal(true);
+        assert!(state.download_modal.0 == DownloadModalInput::Langage);
+
+        // a new search listing nothing keeps the queue modal open
+        state.set_search_result(vec![]);
+        assert!(state.download_modal.0 == DownloadModalInput::Langage);
+
+        let rendered = render(&mut state);
+        assert!(rendered.contains("2 queued katas"));
+    }
+
+    #[test]
+    fn old_settings_migrate_instead_of_resetting() {
+        let dir = std::env::temp_dir().join(format!(
+            "codewars_cli_settings_migrate_{}",
+            std::process::id()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("settings.json");
+        // before the version field and the fields added since
+        fs::write(
+            &path,
+            r#"{"editor_command": "", "download_path": "/home/me/katas", "git_autocommit": true}"#,
+        )
+        .unwrap();
+
+        let (datas, backup) = Settings::read_file(&path).unwrap();
+        assert!(backup.is_none());
+        assert_eq!(datas.version, 0);
+        let datas = datas.migrate();
+        assert_eq!(datas.version, crate::types::SETTINGS_VERSION);
+        assert_eq!(datas.download_path, "/home/me/katas");
+        assert!(datas.git_autocommit);
+        assert_eq!(datas.editor_command, "code"); // saved empty by v0
+        assert_eq!(datas.autocomplete_limit, 20);
+        assert_eq!(datas.max_pages, 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn instruction_is_not_duplicated_across_languages() {
+        let dir = std::env::temp_dir().join(format!("codewars_cli_all_{}", std::process::id()));
+        let dir = dir.to_string_lossy().to_string();
+        let mut state = test_state();
+        let kata = state.source.search(String::new()).await.unwrap().remove(0);
+        let languages = vec!["python".to_string(), "javascript".to_string()];
+
+        let mut terminal = test_terminal();
+        let (kata_path, _) =
+            download_kata(&mut terminal, &mut state, &kata, &languages, &dir, None)
+                .await
+                .unwrap();
+
+        // once, in the index of the kata folder
+        let index = format!("{kata_path}/{}", state.instruction_format.filename());
+        let index = fs::read_to_string(index).unwrap();
+        assert_eq!(index.matches(kata.description.trim()).count(), 1);
+        assert!(index.contains("- [python](python/)"));
+        for language in &languages {
+            let files = fs::read_dir(format!("{kata_path}/{language}"))
+                .unwrap()
+                .map(|entry| entry.unwrap().path())
+                .collect::<Vec<PathBuf>>();
+            assert!(files.iter().any(|file| file.ends_with(format!(
+                "solution{}",
+                language_to_extension(language).unwrap()
+            ))));
+            for file in files {
+                let content = fs::read_to_string(&file).unwrap_or_default();
+                assert!(!content.contains(kata.description.trim()), "{file:?}");
+            }
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn scraped_names_and_tags_are_decoded() {
+        // the title attribute escaped twice, as codewars does it
+        let html = r#"<html><body><main>
+            <div class="list-item-kata" id="k1" data-title="Rock &amp;amp; Roll &amp;#39;n&amp;#39; Katas">
+                <div><span>6 kyu</span></div>
+                <div class="keyword-tag">Data Structures &amp; Algorithms</div>
+                <div class="keyword-tag">Don&#39;t Panic</div>
+            </div>
+        </main></body></html>"#;
+        let katas = crate::datasource::parse_search_page(html);
+        assert_eq!(katas.len(), 1);
+        assert_eq!(katas[0].name, "Rock & Roll 'n' Katas");
+        assert_eq!(
+            katas[0].tags,
+            vec!["Data Structures & Algorithms", "Don't Panic"]
+        );
+    }
+
+    #[tokio::test]
+    async fn mock_source_serves_the_fixtures() {
+        let mut state = test_state();
+        state.submit_search().await;
+        assert!(state.search_status == SearchStatus::Results);
+        assert_eq!(state.search_result.items.len(), 3);
+
+        let listed = state.search_result.items[0].0.clone();
+        let kata = state.source.kata(&listed.id).await.unwrap();
+        assert_eq!(kata.name, listed.name);
+        assert!(kata.description.len() > 0);
+        assert!(matches!(
+            state.source.kata("000000000000000000000000").await,
+            Err(FetchError::NotFound)
+        ));
+
+        let (solution, tests) = state
+            .source
+            .templates(&kata.id, &kata.slug, Some("python"))
+            .await
+            .unwrap();
+        assert!(solution.len() > 0);
+        assert!(tests.len() > 0);
+    }
+
+    #[test]
+    fn dropdown_window_follows_the_selection() {
+        let mut list = StatefulList::with_items((0..20).collect::<Vec<usize>>(), 0);
+
+        // down: the selected item is the last one in view
+        list.state = 12;
+        assert_eq!(list.visible_range(5), 8..13);
+        list.state = 10;
+        assert_eq!(list.visible_range(5), 8..13); // still in view, not scrolled
+
+        // up past the top of the window: the selected item is the first one in view
+        list.state = 7;
+        assert_eq!(list.visible_range(5), 7..12);
+        list.state = 2;
+        assert_eq!(list.visible_range(5), 2..7);
+        list.previous();
+        list.previous();
+        assert_eq!(list.state, 0);
+        assert_eq!(list.visible_range(5), 0..5);
+
+        // wrapping back to the end, then up again
+        list.previous();
+        assert_eq!(list.visible_range(5), 15..20);
+        list.state = 14;
+        assert_eq!(list.visible_range(5), 14..19);
+    }
+
+    #[test]
+    fn path_suggestions_are_capped_and_sorted() {
+        let dir = std::env::temp_dir().join(format!("codewars_cli_ls_{}", std::process::id()));
+        for name in ["gamma", "Beta", "alpha"] {
+            fs::create_dir_all(dir.join(name)).unwrap();
+        }
+        for i in 0..20 {
+            fs::create_dir_all(dir.join(format!("x{i:02}"))).unwrap();
+        }
+        for name in ["Aardvark.txt", "zeta.txt"] {
+            fs::write(dir.join(name), "").unwrap();
+        }
+        let mut state = test_state();
+
+        // the folders first, case insensitive, up to autocomplete_limit
+        state.download_path.push_str(&format!("{}/", dir.display()));
+        state.autocomplete_path();
+        let suggestions = &state.download_path.suggestion.items;
+        assert_eq!(
+            suggestions.len(),
+            SettingsDatas::default().autocomplete_limit
+        );
+        assert_eq!(suggestions[..4], ["alpha", "Beta", "gamma", "x00"]);
+        assert_eq!(suggestions.last().unwrap(), "x16");
+
+        state.download_path.push_str("a");
+        state.autocomplete_path();
+        assert_eq!(
+            state.download_path.suggestion.items,
+            vec!["alpha", "Aardvark.txt"]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn open_preview_loads_the_missing_template() {
+        let mut state = test_state();
+        let mut kata = KataAPI::default();
+        kata.id = "k1".to_string();
+        kata.languages = vec!["python".to_string(), "rust".to_string()];
+        state.set_search_result(vec![kata]);
+        state.open_download_modal(false);
+        let python = state
+            .download_langage
+            .1
+            .items
+            .iter()
+            .position(|(l, _)| l == "python")
+            .unwrap();
+        state.download_langage.1.state = python;
+        // opened on another language, python isn't loaded
+        state.template_preview = true;
+
+        let mut terminal = test_terminal();
+        toggle_template_preview(&mut terminal, &mut state).await;
+        assert!(state.template_preview);
+        assert!(state
+            .template_previews
+            .contains_key(&("k1".to_string(), "python".to_string())));
+
+        // loaded: p closes it
+        toggle_template_preview(&mut terminal, &mut state).await;
+        assert!(!state.template_preview);
+    }
+
+    #[test]
+    fn download_path_is_saved_only_when_remembered() {
+        let settings = SettingsDatas {
+            download_path: "/home/user/katas".to_string(),
+            ..SettingsDatas::default()
+        };
+        assert_eq!(
+            saved_download_path(&settings, "/tmp/elsewhere"),
+            "/home/user/katas"
+        );
+        let settings = SettingsDatas {
+            remember_last_path: true,
+            ..settings
+        };
+        assert_eq!(
+            saved_download_path(&settings, "/tmp/elsewhere"),
+            "/tmp/elsewhere"
+        );
+    }
+
+    #[tokio::test]
+    async fn export_writes_the_listed_todos() {
+        let mut state = test_state();
+        state.set_search_result(katas(2));
+        state.search_status = SearchStatus::Results;
+        state.todo_view = true;
+
+        let file =
+            std::env::temp_dir().join(format!("codewars_cli_export_{}.md", std::process::id()));
+        state.export_search_result(file.to_str().unwrap()).await;
+        let content = fs::read_to_string(&file).unwrap();
+        fs::remove_file(&file).unwrap();
+        assert!(content.contains("kata 0") && content.contains("kata 1"));
+        assert!(!content.contains("Bit Counting")); // the mock search wasn't fetched
+    }
+
+    #[test]
+    fn clicks_follow_the_drawn_fields() {
+        let mut state = test_state();
+        render(&mut state);
+        let search_row = state.field_areas[0].1.y + 1;
+        assert!(state.field_at(4, search_row) == Some(InputMode::Search));
+        assert!(state.field_at(0, search_row).is_none());
+
+        // the collapsed help moves the fields up
+        state.help_collapsed = true;
+        render(&mut state);
+        assert!(state.field_areas[0].1.y + 1 < search_row);
+        assert!(state.field_at(4, state.field_areas[0].1.y + 1) == Some(InputMode::Search));
+        assert!(state.field_at(4, state.field_areas[4].1.y + 1) == Some(InputMode::Tags));
+        assert!(state.field_at(4, search_row) != Some(InputMode::Search));
+    }
+
+    #[tokio::test]
+    async fn id_search_falls_back_to_text_only_when_not_found() {
+        let mut state = test_state();
+        state.search_field.push_str("000000000000000000000000");
+        state.submit_search().await;
+        assert!(state.search_status == SearchStatus::Results);
+        assert_eq!(state.search_result.items.len(), 3); // the text search of the mock
+
+        let mut state = test_state();
+        state.source = Box::new(Unreachable);
+        state.search_field.push_str("000000000000000000000000");
+        state.submit_search().await;
+        assert!(state.search_status == SearchStatus::Failed);
+        assert!(matches!(state.retry, Some(RetryAction::Search)));
+    }
+
+    #[test]
+    fn failed_search_hint_follows_the_retry() {
+        let mut state = test_state();
+        state.search_status = SearchStatus::Failed;
+        state.retry = Some(RetryAction::Search);
+        assert!(render(&mut state).contains("Ctrl+R to retry"));
+
+        state.retry = None;
+        let screen = render(&mut state);
+        assert!(!screen.contains("Ctrl+R to retry"));
+        assert!(screen.contains("search again"));
+    }
+}
@@ -0,0 +1,359 @@
+use std::{
+    cell::{Cell, RefCell},
+    error::Error,
+    sync::Arc,
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use headless_chrome::{Browser, Tab};
+use html_escape::decode_html_entities;
+use scraper::{Html, Selector};
+
+use crate::{
+    types::{KataAPI, SettingsDatas, UserKatas},
+    utils::{
+        codewars_base_url, codewars_token, fetch_codewars_api, fetch_html, fetch_user_katas,
+        kata_url, normalize_code_lines, offline_fallback, offline_katas, FetchError, TextMethods,
+    },
+};
+
+// fixtures served by the mock source, see `--mock`
+const SEARCH_FIXTURE: &str = include_str!("../assets/fixtures/search.json");
+const SOLUTION_FIXTURE: &str = include_str!("../assets/fixtures/solution.txt");
+const TESTS_FIXTURE: &str = include_str!("../assets/fixtures/tests.txt");
+
+// loads of the kata train page before a download fails, waiting SCRAPE_RETRY_DELAY more each time
+const SCRAPE_ATTEMPTS: u32 = 3;
+const SCRAPE_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Where the katas come from: codewars itself, or the bundled fixtures (for dev and demos)
+#[async_trait(?Send)]
+pub trait DataSource {
+    /// katas of a search page (https://www.codewars.com/kata/search)
+    async fn search(&self, url: String) -> Result<Vec<KataAPI>, FetchError>;
+    /// katas of a collection page (https://www.codewars.com/collections/...)
+    async fn collection(&self, url: String) -> Result<Vec<KataAPI>, FetchError>;
+    /// full kata from the API
+    async fn kata(&self, kata_id: &str) -> Result<KataAPI, FetchError>;
+    /// sample code & sample tests of a kata, `slug` can be empty
+    async fn templates(
+        &self,
+        kata_id: &str,
+        slug: &str,
+        langage: Option<&str>,
+    ) -> Result<(Vec<String>, Vec<String>), Box<dyn Error>>;
+    /// katas authored or trained by the codewars user `username`
+    async fn user_katas(&self, username: &str, kind: UserKatas)
+        -> Result<Vec<KataAPI>, FetchError>;
+    /// apply the settings of the source, at startup and on reload
+    fn configure(&mut self, _settings: &SettingsDatas) {}
+    /// the templates fetched until the matching end_batch share their resources (browser), batches can nest
+    fn begin_batch(&self) {}
+    /// see begin_batch, the resources are released by the outermost one
+    fn end_batch(&self) {}
+}
+
+pub struct Live {
+    navigation_timeout: Duration,      // loading of the kata train page
+    element_timeout: Duration,         // rendering of the code editors once the page is loaded
+    token: Option<String>,             // api token, see `codewars_token`
+    browser: RefCell<Option<Browser>>, // shared by the templates of a batch
+    batch_depth: Cell<usize>,
+}
+pub struct Mock;
+
+/// `--mock` flag or `CODEWARS_CLI_MOCK` env variable
+pub fn from_args() -> Box<dyn DataSource> {
+    let mock_flag = std::env::args().any(|arg| arg == "--mock");
+    if mock_flag || std::env::var("CODEWARS_CLI_MOCK").is_ok() {
+        Box::new(Mock)
+    } else {
+        let defaults = SettingsDatas::default();
+        Box::new(Live {
+            navigation_timeout: Duration::from_secs(defaults.navigation_timeout),
+            element_timeout: Duration::from_secs(defaults.element_timeout),
+            token: codewars_token(&defaults.api_token),
+            browser: RefCell::new(None),
+            batch_depth: Cell::new(0),
+        })
+    }
+}
+
+impl Live {
+    /// new tab of the browser of the batch, launched by the first one
+    fn batch_tab(&self) -> Result<Arc<Tab>, Box<dyn Error>> {
+        let mut browser = self.browser.borrow_mut();
+        if browser.is_none() {
+            *browser = Some(Browser::default()?);
+        }
+        match browser.as_ref() {
+            Some(browser) => Ok(browser.new_tab()?),
+            None => Err("failed to launch the browser".into()),
+        }
+    }
+
+    /// code of the solution and tests editors of the train page `url`
+    async fn scrape_editors(
+        &self,
+        tab: &Tab,
+        url: &str,
+    ) -> Result<(Vec<String>, Vec<String>), Box<dyn Error>> {
+        // the page or its editors are sometimes slow to come, the page is loaded again before giving up
+        let mut failure = String::new();
+        for attempt in 0..SCRAPE_ATTEMPTS {
+            if attempt > 0 {
+                tokio::time::sleep(SCRAPE_RETRY_DELAY * attempt).await;
+            }
+
+            let navigated = tab
+                .navigate_to(url)
+                .and_then(|tab| tab.wait_until_navigated());
+            if let Err(_) = navigated {
+                failure = format!(
+                    "the kata page didn't load in {}s",
+                    self.navigation_timeout.as_secs()
+                );
+                continue;
+            }
+
+            let editors = editor_lines(
+                tab,
+                "#code div.CodeMirror-code > div > pre",
+                self.element_timeout,
+            )
+            .and_then(|solution_field_lines| {
+                let tests_field_lines = editor_lines(
+                    tab,
+                    "#fixture div.CodeMirror-code > div > pre",
+                    self.element_timeout,
+                )?;
+                Ok((solution_field_lines, tests_field_lines))
+            });
+            match editors {
+                Ok(lines) => return Ok(lines),
+                Err(why) => failure = why.to_string(),
+            }
+        }
+
+        Err(format!("{failure} ({SCRAPE_ATTEMPTS} attempts)").into())
+    }
+}
+
+#[async_trait(?Send)]
+impl DataSource for Live {
+    async fn search(&self, url: String) -> Result<Vec<KataAPI>, FetchError> {
+        let html_doc = fetch_html(url).await?;
+        Ok(parse_search_page(html_doc.as_str()))
+    }
+
+    async fn collection(&self, url: String) -> Result<Vec<KataAPI>, FetchError> {
+        // the katas of a collection are listed the same way as the search ones
+        let html_doc = fetch_html(url).await?;
+        Ok(parse_search_page(html_doc.as_str()))
+    }
+
+    async fn kata(&self, kata_id: &str) -> Result<KataAPI, FetchError> {
+        // offline: the kata may have been saved before, see `save_offline`
+        offline_fallback(
+            fetch_codewars_api(kata_id, self.token.as_deref()).await,
+            kata_id,
+            offline_katas,
+        )
+    }
+
+    async fn templates(
+        &self,
+        kata_id: &str,
+        slug: &str,
+        langage: Option<&str>,
+    ) -> Result<(Vec<String>, Vec<String>), Box<dyn Error>> {
+        // one chrome per call, unless in a batch
+        let own_browser = match self.batch_depth.get() {
+            0 => Some(Browser::default()?),
+            _ => None,
+        };
+        let tab = match &own_browser {
+            Some(browser) => browser.new_tab()?,
+            None => self.batch_tab()?,
+        };
+        tab.set_default_timeout(self.navigation_timeout);
+        let url = format!(
+            "{}/train{}",
+            kata_url(kata_id, slug),
+            match langage {
+                Some(l) => "/".to_string() + l,
+                None => String::new(),
+            }
+        );
+
+        let result = self.scrape_editors(&tab, &url).await;
+        if own_browser.is_none() {
+            // the batch browser stays open for the next language
+            if let Err(_) = tab.close(false) {}
+        }
+        result
+    }
+
+    async fn user_katas(
+        &self,
+        username: &str,
+        kind: UserKatas,
+    ) -> Result<Vec<KataAPI>, FetchError> {
+        fetch_user_katas(username, kind, self.token.as_deref()).await
+    }
+
+    fn configure(&mut self, settings: &SettingsDatas) {
+        self.navigation_timeout = Duration::from_secs(settings.navigation_timeout);
+        self.element_timeout = Duration::from_secs(settings.element_timeout);
+        self.token = codewars_token(&settings.api_token);
+    }
+
+    fn begin_batch(&self) {
+        self.batch_depth.set(self.batch_depth.get() + 1);
+    }
+
+    fn end_batch(&self) {
+        self.batch_depth
+            .set(self.batch_depth.get().saturating_sub(1));
+        if self.batch_depth.get() == 0 {
+            // closes chrome
+            self.browser.borrow_mut().take();
+        }
+    }
+}
+
+/// lines of a CodeMirror editor of the page, waiting at most `timeout` for it to be rendered
+fn editor_lines(
+    tab: &Tab,
+    selector: &str,
+    timeout: Duration,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    if let Err(_) = tab.wait_for_element_with_custom_timeout(selector, timeout) {
+        return Err(format!(
+            "the page loaded but not its code editor (waited {}s)",
+            timeout.as_secs()
+        )
+        .into());
+    }
+
+    match tab.find_elements(selector) {
+        Ok(lines) => Ok(normalize_code_lines(
+            lines
+                .iter()
+                .map(|line| line.get_inner_text().unwrap_or_default())
+                .collect::<Vec<String>>(),
+        )),
+        Err(_) => Err("failed to get the code sample".into()),
+    }
+}
+
+#[async_trait(?Send)]
+impl DataSource for Mock {
+    async fn search(&self, _url: String) -> Result<Vec<KataAPI>, FetchError> {
+        Ok(serde_json::from_str::<Vec<KataAPI>>(SEARCH_FIXTURE)?)
+    }
+
+    async fn collection(&self, _url: String) -> Result<Vec<KataAPI>, FetchError> {
+        Ok(serde_json::from_str::<Vec<KataAPI>>(SEARCH_FIXTURE)?)
+    }
+
+    async fn user_katas(
+        &self,
+        _username: &str,
+        _kind: UserKatas,
+    ) -> Result<Vec<KataAPI>, FetchError> {
+        Ok(serde_json::from_str::<Vec<KataAPI>>(SEARCH_FIXTURE)?)
+    }
+
+    async fn kata(&self, kata_id: &str) -> Result<KataAPI, FetchError> {
+        let katas = serde_json::from_str::<Vec<KataAPI>>(SEARCH_FIXTURE)?;
+        match katas.into_iter().find(|kata| kata.id == kata_id) {
+            Some(kata) => Ok(kata),
+            None => Err(FetchError::NotFound),
+        }
+    }
+
+    async fn templates(
+        &self,
+        _kata_id: &str,
+        _slug: &str,
+        _langage: Option<&str>,
+    ) -> Result<(Vec<String>, Vec<String>), Box<dyn Error>> {
+        let to_lines = |fixture: &str| fixture.lines().map(|l| l.to_string()).collect();
+        Ok((to_lines(SOLUTION_FIXTURE), to_lines(TESTS_FIXTURE)))
+    }
+}
+
+/// scrap the katas of a codewars search page
+pub fn parse_search_page(html_doc: &str) -> Vec<KataAPI> {
+    let document = Html::parse_document(html_doc);
+
+    let kata_selector = Selector::parse("main .list-item-kata").unwrap();
+    let tags_selector = Selector::parse(".keyword-tag").unwrap();
+    let languages_selector = Selector::parse("div div:nth-child(2) li a").unwrap();
+    let author_selector = Selector::parse("a[data-tippy-content=\"This kata's Sensei\"]").unwrap();
+    let total_completed_selector =
+        Selector::parse("span[data-tippy-content=\"Total times this kata has been completed\"]")
+            .unwrap();
+    let rank_selector = Selector::parse("span").unwrap(); // only the first item
+
+    let mut katas: Vec<KataAPI> = vec![];
+    for element in document.select(&kata_selector) {
+        let mut kata = KataAPI::default();
+
+        kata.id = element.value().id().unwrap_or_default().to_string();
+        kata.url = kata_url(&kata.id, "");
+        // the title can be escaped twice (e.g: "&amp;#39;"), so decode what the parser left
+        kata.name = decode_html_entities(element.value().attr("data-title").unwrap_or_default())
+            .to_string();
+
+        for tag_elem in element.select(&tags_selector) {
+            kata.tags
+                .push(decode_html_entities(&tag_elem.text().to_string()).to_string());
+        }
+
+        for language_elem in element.select(&languages_selector) {
+            kata.languages.push(
+                language_elem
+                    .value()
+                    .attr("data-language")
+                    .unwrap_or_default()
+                    .to_string(),
+            )
+        }
+
+        if let Some(elem) = element.select(&author_selector).next() {
+            kata.createdBy.username = elem.text().to_string();
+            kata.createdBy.url = match elem.value().attr("href") {
+                Some(href) if href.starts_with("/") => format!("{}{href}", codewars_base_url()),
+                Some(href) if href.len() > 0 => href.to_string(),
+                _ => format!("{}/users/{}", codewars_base_url(), kata.createdBy.username),
+            };
+        }
+
+        kata.totalCompleted = match element.select(&total_completed_selector).next() {
+            Some(elem) => elem
+                .text()
+                .to_string()
+                .replace(",", "")
+                .parse::<usize>()
+                .unwrap_or_default(),
+            None => 0,
+        };
+
+        kata.rank.name = match element.select(&rank_selector).next() {
+            Some(elem) => elem.text().to_string(),
+            None => String::new(),
+        };
+        // beta katas have no rank, their badge says "Beta" instead
+        if kata.rank.name.trim().eq_ignore_ascii_case("beta") {
+            kata.beta = true;
+            kata.rank.name = String::new();
+        }
+
+        katas.push(kata);
+    }
+
+    return katas;
+}
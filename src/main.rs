@@ -1,4 +1,6 @@
 pub mod app;
+pub mod datasource;
+pub mod paths;
 pub mod types;
 pub mod ui;
 pub mod utils;
@@ -23,23 +25,40 @@ use types::CodewarsCLI;
  */
 
 const TERMINAL_REF_SIZE: (u16, u16) = (147, 34);
+// some pseudo-terminals (CI...) report a 0x0 size
+const TERMINAL_MIN_SIZE: (u16, u16) = (20, 10);
+
+/// leave raw mode and the alternate screen, to give the shell back in a usable state
+fn restore_terminal() -> Result<(), Box<dyn Error>> {
+    disable_raw_mode()?;
+    execute!(std::io::stdout(), LeaveAlternateScreen, DisableMouseCapture)?;
+    Ok(())
+}
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
-    let mut state = CodewarsCLI::new();
+    // restore the terminal before printing the panic, otherwise it's garbled (and so is the shell)
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if let Err(_) = restore_terminal() {}
+        default_hook(info);
+    }));
+
+    let mut state = CodewarsCLI::new(datasource::from_args());
+    // mouse capture prevents selecting text in the terminal, `--no-mouse` (or `"mouse": false`) gives it back
+    state.mouse = !std::env::args().any(|arg| arg == "--no-mouse")
+        && state.settings.value().unwrap_or_default().mouse;
     enable_raw_mode()?;
-    execute!(std::io::stdout(), EnterAlternateScreen, EnableMouseCapture)?;
+    execute!(std::io::stdout(), EnterAlternateScreen)?;
+    if state.mouse {
+        execute!(std::io::stdout(), EnableMouseCapture)?;
+    }
     let backend = CrosstermBackend::new(std::io::stdout());
     let mut terminal = Terminal::new(backend)?;
 
     let result = run_app(&mut terminal, &mut state).await;
 
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
+    restore_terminal()?;
 
     if let Err(e) = result {
         println!("{}", e.to_string());
@@ -0,0 +1,106 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::utils::get_uname;
+
+const APP_DIR: &str = "codewars_cli";
+
+/// `<base>/codewars_cli`, created if needed. Fallback to the old `~/.cache/codewars_cli` when the platform dir is unknown
+fn app_dir(base: Option<PathBuf>) -> Result<PathBuf, String> {
+    let dir = match base {
+        Some(base) => base.join(APP_DIR),
+        None => legacy_dir(),
+    };
+
+    if let Err(why) = fs::create_dir_all(&dir) {
+        return Err(why.to_string());
+    }
+    Ok(dir)
+}
+
+/// before the config/cache/data split, everything was in there
+fn legacy_dir() -> PathBuf {
+    let uname = get_uname();
+    Path::new(&format!("/home/{uname}/.cache")).join(APP_DIR)
+}
+
+/// settings.json, in the config dir (e.g: ~/.config/codewars_cli)
+pub fn config_file() -> Result<PathBuf, String> {
+    let path = app_dir(dirs::config_dir())?.join("settings.json");
+
+    // move the settings of the older versions to their new place
+    let legacy_path = legacy_dir().join("settings.json");
+    if !path.exists() && legacy_path.exists() && legacy_path != path {
+        if let Err(_) = fs::rename(&legacy_path, &path) {}
+    }
+
+    Ok(path)
+}
+
+/// dev logs, in the cache dir (e.g: ~/.cache/codewars_cli)
+pub fn log_file() -> Result<PathBuf, String> {
+    cache_file("dev_logs.log")
+}
+
+/// disposable files (logs, html dumps...), in the cache dir (e.g: ~/.cache/codewars_cli)
+pub fn cache_file(name: &str) -> Result<PathBuf, String> {
+    Ok(app_dir(dirs::cache_dir())?.join(name))
+}
+
+/// user datas (history, favorites...), in the data dir (e.g: ~/.local/share/codewars_cli)
+pub fn data_file(name: &str) -> Result<PathBuf, String> {
+    Ok(app_dir(dirs::data_dir())?.join(name))
+}
+
+/// size (in bytes) of the settings and of every file of the cache dir
+pub fn cache_report() -> Result<Vec<(String, u64)>, String> {
+    let settings_path = config_file()?;
+    let mut report = vec![(
+        "settings.json (kept)".to_string(),
+        fs::metadata(&settings_path).map(|m| m.len()).unwrap_or(0),
+    )];
+
+    for path in cache_dir_files()? {
+        if path == settings_path {
+            continue;
+        }
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        report.push((name.to_string(), size));
+    }
+    Ok(report)
+}
+
+/// delete every file of the cache dir but the settings, returns how many were deleted
+pub fn clear_cache() -> Result<usize, String> {
+    let settings_path = config_file()?;
+
+    let mut deleted = 0;
+    for path in cache_dir_files()? {
+        if path == settings_path {
+            continue; // same dir with the legacy layout
+        }
+        if let Err(why) = fs::remove_file(&path) {
+            return Err(why.to_string());
+        }
+        deleted += 1;
+    }
+    Ok(deleted)
+}
+
+fn cache_dir_files() -> Result<Vec<PathBuf>, String> {
+    let entries = match fs::read_dir(app_dir(dirs::cache_dir())?) {
+        Ok(entries) => entries,
+        Err(why) => return Err(why.to_string()),
+    };
+
+    let mut files = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .collect::<Vec<PathBuf>>();
+    files.sort();
+    Ok(files)
+}
@@ -1,11 +1,20 @@
-use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    time::Instant,
+};
+
+use arboard::Clipboard;
+use serde::{Deserialize, Deserializer, Serialize};
+use tui::{layout::Rect, style::Color};
 
 use crate::{
     app::Settings,
+    datasource::DataSource,
     ui::{InputWidget, StatefulList},
+    utils::ColorSource,
 };
 
-#[derive(PartialEq)]
+#[derive(PartialEq, Clone, Copy)]
 pub enum InputMode {
     Normal,
     Search,
@@ -25,11 +34,194 @@ pub enum DownloadModalInput {
     Submit,
 }
 
+#[derive(PartialEq)]
+pub enum SearchStatus {
+    NeverSearched,
+    Loading,
+    Empty,
+    Failed,
+    LoginRequired, // codewars answered with its sign in page
+    Results,
+}
+
+// file format of the downloaded kata instruction
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum InstructionFormat {
+    Markdown,
+    Html,
+    Text,
+}
+
+impl InstructionFormat {
+    pub fn next(self) -> Self {
+        match self {
+            InstructionFormat::Markdown => InstructionFormat::Html,
+            InstructionFormat::Html => InstructionFormat::Text,
+            InstructionFormat::Text => InstructionFormat::Markdown,
+        }
+    }
+
+    pub fn filename(self) -> &'static str {
+        match self {
+            InstructionFormat::Markdown => "README.md",
+            InstructionFormat::Html => "README.html",
+            InstructionFormat::Text => "README.txt",
+        }
+    }
+}
+
+// what the editor opens after a download
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum EditorScope {
+    Folder,   // the kata folder
+    Solution, // the solution file, the folder when several languages are downloaded at once
+}
+
+// a kata downloaded by the user, saved in the data dir
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DownloadRecord {
+    pub kata_id: String,
+    pub languages: Vec<String>,
+    pub path: String,
+    pub downloaded_at: u64, // unix timestamp
+}
+
+// kata bookmarked to be worked on later, saved in the data dir
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TodoEntry {
+    pub kata_id: String,
+    pub name: String,
+    pub note: String,
+    pub added_at: u64, // unix timestamp
+}
+
+// kata of the day, saved in the data dir so that it stays the same all day long
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DailyKata {
+    pub date: String, // YYYY-MM-DD
+    pub kata_id: String,
+}
+
+// network action that failed, kept to be re-run as is
+pub enum RetryAction {
+    Search,
+    Download {
+        kata: Box<KataAPI>, // the list may have changed since the failure
+        languages: Vec<String>,
+        udownload_path: String,
+        editor: String,
+    },
+    Collection(String),
+    UserKatas(UserKatas),
+}
+
+// katas of the user account listed instead of the search
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum UserKatas {
+    Authored,
+    Trained,
+}
+
+// what a key of the normal mode or of the list of katas does, see the keymaps of app.rs
+#[derive(Clone, Copy, PartialEq)]
+pub enum KeyAction {
+    Quit,
+    Focus(InputMode),
+    OpenCollection,
+    CacheView,
+    LogView,
+    CopyFilters,
+    ImportFilters,
+    ReloadSettings,
+    KataOfTheDay,
+    ImportSolved,
+    Todos,
+    ToggleBeta,
+    PinFilters,
+    ClearFilters,
+    NarrowPanel,
+    WidenPanel,
+    ToggleHelp,
+    ListUserKatas(UserKatas),
+    // list of katas
+    NextKata,
+    PreviousKata,
+    RowDown,
+    RowUp,
+    OpenKata,
+    CopyDescription,
+    ScratchFile,
+    Export,
+    CopyId,
+    CopyNames,
+    CopyTrainCommand,
+    CopySampleTests,
+    ToggleSolved,
+    LoadMore,
+    ToggleIds,
+    SaveOffline,
+    RevealDownloaded,
+    AddTodo,
+    RemoveTodo,
+    AuthorProfile,
+    AuthorFilter,
+    AllAuthors,
+    Download,
+    Queue,
+    DownloadQueue,
+}
+
+impl UserKatas {
+    /// path of the list in the users api
+    pub fn endpoint(&self) -> &'static str {
+        match self {
+            UserKatas::Authored => "authored",
+            UserKatas::Trained => "completed",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            UserKatas::Authored => "authored",
+            UserKatas::Trained => "trained",
+        }
+    }
+}
+
+// what the prompt input is asked for
+pub enum PromptKind {
+    Export,
+    Collection,
+    ImportSolved,
+    TodoNote,
+    ImportFilters,
+}
+
+pub enum MessageKind {
+    Info,
+    Success,
+    Error,
+}
+
+// line of a diff between a file on disk and its fresh version
+#[derive(Debug, PartialEq)]
+pub enum DiffLine {
+    Same(String),
+    Removed(String),
+    Added(String),
+}
+
+// files the download would overwrite, with their diff
+pub type ChangedFiles = Vec<(String, Vec<DiffLine>)>;
+
 pub enum CursorDirection {
     RIGHT,
     LEFT,
 }
 
+// pseudo language of the download modal, to download the kata in every languages at once
+pub const ALL_LANGUAGES: &str = "All languages";
+
 // for endpoint: &r%5B%5D=-8&r%5B%5D=-6 (decoded: "&r[]=-8&r[]=-6", here for kyu 8 and 6) // thus it's just the "state.difficulty_field"
 pub const DIFFICULTY: [&str; 9] = [
     "Select Ranks", // do nothing
@@ -238,70 +430,335 @@ pub const TAGS: [&str; 109] = [
 pub struct CodewarsCLI {
     // client/framework state
     pub terminal_size: (u16, u16),
+    pub split_ratio: u16, // width (in %) of the search section, the list takes the rest
+    pub list_columns: usize, // columns of katas cards, from the settings
+    pub grid_columns: usize, // columns drawn: list_columns when the list is wide enough for them
+    pub wrap_navigation: bool,
+    pub help_collapsed: bool, // the keys help is a single line, to give room to the fields
+    pub mouse: bool,          // mouse capture is enabled
+    pub quit_requested: bool, // Ctrl+C during a long action, run_app quits once it's stopped
+    pub banner_colors: Box<dyn ColorSource>, // random, unless the banner must be reproducible (tests)
+    pub banner: Option<[Color; 3]>, // colors of the banner words, picked on the first draw and after a resize
+    pub no_color: bool,             // NO_COLOR env variable is set
+    pub source: Box<dyn DataSource>, // live codewars or mocked fixtures
+    pub clipboard: Option<Clipboard>, // kept for the app lifetime, the copied content lives with it on X11
     // app state
     pub settings: Settings,
     pub input_mode: InputMode,
+    pub message: Option<(MessageKind, String)>, // feedback to the user, cleared on the next key press
+    pub message_expiry: Option<Instant>,        // success messages also go away by themselves
+    pub retry: Option<RetryAction>,             // last failed action, if it can be retried
+    pub cache_view: Option<Vec<(String, u64)>>, // files of the app and their size, shown over the list
+    pub log_view: Option<(Vec<String>, usize)>, // tail of the log file and how many lines it's scrolled up, shown over the list
+    pub overwrite_diff: Option<(ChangedFiles, usize)>, // (changed files, scroll) waiting for the user to confirm the download
+    pub prompt: Option<(PromptKind, InputWidget)>,     // one line input over the list section
     pub search_result: StatefulList<(KataAPI, usize)>,
+    pub search_status: SearchStatus,
+    pub last_submitted_query: Option<String>, // query and filters of the listed search, see search_signature
     pub field_dropdown: (bool, StatefulList<(String, usize)>),
+    pub dropdown_filter: InputWidget, // search line of the field dropdown
+    pub dropdown_sorted: bool,        // languages/tags dropdown in alphabetical order
+    pub field_areas: Vec<(InputMode, Rect)>, // where the search fields were last drawn, hit by the mouse clicks
     // download page
     pub download_modal: (DownloadModalInput, usize),
+    pub download_warning: Option<String>, // the kata of the modal was already downloaded
+    pub download_queue: Vec<KataAPI>,     // katas to download at once
+    pub queue_modal: bool,                // the download modal is for the queue
+    pub download_progress: Option<(usize, usize, String)>, // (current, total, language) while downloading
     pub download_path: InputWidget,
     pub editor_field: InputWidget,
+    pub instruction_format: InstructionFormat,
     pub download_langage: (bool, StatefulList<(String, usize)>),
+    pub download_langage_locked: bool, // locked to the language filter of the search
+    pub languages_cache: HashMap<String, Vec<String>>, // kata id -> languages of the api, the search page can miss some
+    pub languages_loading: bool,
+    pub template_previews: HashMap<(String, String), Vec<String>>, // (kata id, language) -> solution template
+    pub template_preview: bool, // the solution template is previewed in the download modal
+    pub fetched_templates: HashMap<(String, String), (Vec<String>, Vec<String>)>, // (kata id, language) -> (solution, tests) compared before the download
+    pub template_preview_loading: bool,
     // fields state
     pub search_field: InputWidget,
+    pub author_filter: Option<String>, // client side filter on the kata sensei
+    pub hide_beta: bool,               // client side filter on the beta katas
+    pub collection: Option<String>,    // url of the collection listed instead of the search
+    pub solved: HashSet<String>,       // ids of the katas the user marked as solved
+    pub todos: Vec<TodoEntry>,
+    pub todo_view: bool, // the to-dos are listed instead of the search
+    pub show_ids: bool,  // the kata ids are shown next to their names
+    pub user_view: Option<UserKatas>, // the katas of the user are listed instead of the search
+    pub field_hints: [Option<String>; 5], // shown in the search, sort by, language, difficulty and tags titles
+    pub loaded_pages: usize, // search pages listed, the next one is appended by load_more (0 when not a search)
     pub sortby_field: usize,
+    pub sort_reversed: bool, // the direction of the sort is flipped (e.g: most completed becomes least completed)
     pub langage_field: usize,
     pub difficulty_field: usize,
     pub tag_field: usize,
 }
 
+// bump this when the shape of SettingsDatas changes, and add a step in SettingsDatas::migrate
+pub const SETTINGS_VERSION: u32 = 1;
+// min and max width (in %) of the search section
+pub const SPLIT_RATIO_BOUNDS: (u16, u16) = (20, 50);
+
 #[derive(Serialize, Deserialize, Clone)]
+#[serde(default)] // missing fields (older settings files) fallback to the default values
 pub struct SettingsDatas {
+    #[serde(default)]
+    // files written before versioning have no version, so it must be 0 and not SETTINGS_VERSION
+    pub version: u32,
     pub editor_command: String,
+    pub editor_scope: EditorScope, // open the kata folder or its solution file after a download
     pub download_path: String,
+    pub remember_last_path: bool, // keep the path typed in the download modal instead of resetting it to download_path
+    pub git_autocommit: bool,     // commit the downloaded kata when it lands in a git repository
+    pub batch_open_editor: bool, // open the download path in the editor once after a queue download, never if false
+    pub allow_empty_templates: bool, // write the sample files even when nothing was scraped, instead of failing the download
+    pub write_gitignore: bool, // write a .gitignore of the language build artifacts in the kata folder
+    pub write_manifest: bool, // write the manifest of `manifests` of the language in the kata folder
+    pub manifests: HashMap<String, ManifestTemplate>, // language slug -> project manifest
+    pub readme_header: String, // prepended to the markdown README, `{name}`, `{rank}`, `{url}`, `{tags}` and `{author}` are replaced, empty for none
+    pub autocomplete_limit: usize, // max number of path suggestions
+    pub my_languages: Vec<String>, // languages slugs searched when "My Languages" is selected
+    pub split_ratio: u16,      // width (in %) of the search section
+    pub list_columns: usize,   // columns of katas cards on wide terminals
+    pub instruction_format: InstructionFormat,
+    pub preinstall: HashMap<String, PreinstallHook>, // language slug -> user scaffolding command
+    pub scaffold: HashMap<String, ScaffoldLevel>, // language slug -> how much project is set up around the sample files (Full when missing)
+    // filters preselected at startup, indexes of DIFFICULTY and TAGS (0 is no filter)
+    pub default_difficulty: usize,
+    pub default_tag: usize,
+    pub max_pages: usize,        // search pages fetched by a search
+    pub export_max_pages: usize, // search pages fetched by an export
+    pub wrap_navigation: bool, // going down on the last kata/dropdown item goes back to the first one
+    pub help_collapsed: bool,  // toggled with h
+    pub mouse: bool, // capture the mouse (clicks, wheel), false lets the terminal select text
+    // in seconds, raise them on slow connections if downloads fail to get the code sample
+    pub navigation_timeout: u64,
+    pub element_timeout: u64,
+    pub train_command: String, // copied with R, `{id}`, `{slug}`, `{language}` and `{url}` are replaced
+    pub api_token: String, // the CODEWARS_TOKEN env variable is preferred, to not store it in plain text
+    pub username: String,  // codewars account of the token, to list its authored and trained katas
 }
 
-impl SettingsDatas {
-    pub fn default() -> Self {
+impl Default for SettingsDatas {
+    fn default() -> Self {
         Self {
+            version: SETTINGS_VERSION,
             editor_command: "code".to_string(),
+            editor_scope: EditorScope::Folder,
             download_path: String::new(),
+            remember_last_path: false,
+            git_autocommit: false,
+            batch_open_editor: true,
+            allow_empty_templates: false,
+            write_gitignore: false,
+            write_manifest: false,
+            manifests: default_manifests(),
+            readme_header: DEFAULT_README_HEADER.to_string(),
+            autocomplete_limit: 20,
+            my_languages: vec![],
+            split_ratio: 30,
+            list_columns: 1,
+            instruction_format: InstructionFormat::Markdown,
+            preinstall: HashMap::new(),
+            scaffold: HashMap::new(),
+            default_difficulty: 0,
+            default_tag: 0,
+            max_pages: 1,
+            export_max_pages: 5,
+            wrap_navigation: true,
+            help_collapsed: false,
+            mouse: true,
+            navigation_timeout: 30,
+            element_timeout: 20,
+            api_token: String::new(),
+            username: String::new(),
+            train_command: "{url}/train/{language}".to_string(),
         }
     }
 }
 
+// user command run in the kata folder before writing the sample files, e.g:
+// "python": { "command": "poetry new --src {path}", "solution_dir": "src/" }
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct PreinstallHook {
+    pub command: String,      // `{path}` is replaced by the kata folder
+    pub solution_dir: String, // where the sample files go, relative to the kata folder
+}
+
+// project set up around the sample files of a language, e.g: "rust": "None" for a lone solution.rs
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum ScaffoldLevel {
+    None,    // no command, the sample files at the top of the kata folder
+    Minimal, // rust: `cargo init --vcs none` (no git repository), the preinstall command otherwise
+    Full,    // rust: `cargo init`, the preinstall command otherwise
+}
+
+// project manifest written next to the sample files, e.g:
+// "javascript": { "file": "package.json", "content": "{ \"name\": \"{name}\" }" }
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct ManifestTemplate {
+    pub file: String,    // relative to the kata folder
+    pub content: String, // `{name}` is replaced by the kata slug
+}
+
+/// yaml front matter, rendered by most markdown tools and static site generators
+const DEFAULT_README_HEADER: &str =
+    "---\nname: \"{name}\"\nrank: \"{rank}\"\nurl: {url}\ntags: [{tags}]\nauthor: \"{author}\"\n---";
+
+/// manifests of the languages with a package manager but no scaffolding command, rust has `cargo init`
+fn default_manifests() -> HashMap<String, ManifestTemplate> {
+    let package_json = |test: &str, dev_dependencies: &[&str]| ManifestTemplate {
+        file: "package.json".to_string(),
+        content: [
+            "{".to_string(),
+            "  \"name\": \"{name}\",".to_string(),
+            "  \"private\": true,".to_string(),
+            format!("  \"scripts\": {{ \"test\": \"{test}\" }},"),
+            format!(
+                "  \"devDependencies\": {{ {} }}",
+                dev_dependencies.join(", ")
+            ),
+            "}\n".to_string(),
+        ]
+        .join("\n"),
+    };
+
+    HashMap::from([
+        (
+            "javascript".to_string(),
+            package_json(
+                "mocha tests.js",
+                &["\"chai\": \"^4.3.7\"", "\"mocha\": \"^10.2.0\""],
+            ),
+        ),
+        (
+            "typescript".to_string(),
+            package_json(
+                "mocha -r ts-node/register tests.ts",
+                &[
+                    "\"@types/chai\": \"^4.3.4\"",
+                    "\"@types/mocha\": \"^10.0.1\"",
+                    "\"chai\": \"^4.3.7\"",
+                    "\"mocha\": \"^10.2.0\"",
+                    "\"ts-node\": \"^10.9.1\"",
+                    "\"typescript\": \"^4.9.5\"",
+                ],
+            ),
+        ),
+    ])
+}
+
+impl SettingsDatas {
+    /// scaffold level of `language`, see `scaffold`
+    pub fn scaffold_level(&self, language: &str) -> ScaffoldLevel {
+        match self.scaffold.get(language) {
+            Some(level) => *level,
+            None => ScaffoldLevel::Full,
+        }
+    }
+
+    /// upgrade settings written by an older version of the app, step by step
+    pub fn migrate(mut self) -> Self {
+        if self.version < 1 {
+            // v0: no version field, and the editor could be saved empty
+            if self.editor_command.trim().len() <= 0 {
+                self.editor_command = "code".to_string();
+            }
+        }
+
+        self.version = SETTINGS_VERSION;
+        return self;
+    }
+}
+
+/// the api gives null instead of leaving the field out (e.g: the rank of a beta kata)
+fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + Default,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
 // Minified katas from search result (https://www.codewars.com/kata/search)
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 #[allow(non_snake_case)]
 pub struct KataAPI {
-    pub id: String,             // ID of the kata.
-    pub name: String,           // Name of the kata.
-    pub slug: String,           // Slug of the kata.
-    pub url: String,            // URL of the kata.
-    pub category: String,       // Category of the kata.
-    pub description: String,    // Description of the kata in Markdown.
-    pub tags: Vec<String>,      // Array of tags associated with the kata.
+    pub id: String,   // ID of the kata.
+    pub name: String, // Name of the kata.
+    // the fields below can be missing (search page scrapping, old or beta katas...)
+    #[serde(default)]
+    pub slug: String, // Slug of the kata.
+    #[serde(default)]
+    pub url: String, // URL of the kata.
+    #[serde(default)]
+    pub category: String, // Category of the kata.
+    #[serde(default)]
+    pub description: String, // Description of the kata in Markdown.
+    #[serde(default)]
+    pub tags: Vec<String>, // Array of tags associated with the kata.
+    #[serde(default)]
     pub languages: Vec<String>, // Array of language names the kata is available in.
+    #[serde(default, deserialize_with = "null_as_default")]
     pub rank: APIRank,
+    #[serde(default)]
     pub createdBy: APIAuthor,
+    #[serde(default, deserialize_with = "null_as_default")]
     pub publishedAt: String,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub approvedAt: String, // null for beta katas
+    #[serde(default)]
+    pub beta: bool, // not approved yet, no rank and tests that may change
+    #[serde(default)]
     pub totalCompleted: usize,
+    #[serde(default)]
     pub totalAttempts: usize,
+    #[serde(default)]
     pub totalStars: usize,
+    #[serde(default)]
     pub voteScore: usize,
     // this struct is imcomplete, see https://dev.codewars.com/#get-code-challenge
 }
 
-#[derive(Deserialize)]
+// kata of a user list (https://dev.codewars.com/#list-authored-challenges), not shaped like KataAPI
+#[derive(Deserialize, Default)]
+#[serde(default)]
+#[allow(non_snake_case)]
+pub struct APIUserKata {
+    pub id: String,
+    pub name: String,
+    pub slug: String,
+    pub description: String,
+    #[serde(deserialize_with = "null_as_default")]
+    pub rankName: String, // authored only, null while in beta
+    pub tags: Vec<String>,
+    pub languages: Vec<String>,          // authored only
+    pub completedLanguages: Vec<String>, // trained only
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct APIUserKatas {
+    pub data: Vec<APIUserKata>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
 pub struct APIAuthor {
     pub username: String,
     pub url: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
 pub struct APIRank {
+    #[serde(deserialize_with = "null_as_default")]
     pub id: isize,
+    #[serde(deserialize_with = "null_as_default")]
     pub name: String,
+    #[serde(deserialize_with = "null_as_default")]
     pub color: String,
 }
@@ -1,38 +1,71 @@
+use std::ops::Range;
+
 use tui::{
     backend::Backend,
+    buffer::Buffer,
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Span, Spans},
-    widgets::{Block, BorderType, Borders, List, ListItem, Paragraph, Wrap},
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem, Paragraph, Widget, Wrap},
     Frame,
 };
 
 use crate::{
+    app::{KATA_LIST_KEYMAP, NORMAL_KEYMAP, SEARCH_MAX_LEN},
     types::{
-        CodewarsCLI, CursorDirection, DownloadModalInput, InputMode, KataAPI, DIFFICULTY, LANGAGE,
-        SORT_BY, TAGS,
+        CodewarsCLI, CursorDirection, DiffLine, DownloadModalInput, InputMode, KataAPI,
+        MessageKind, PromptKind, SearchStatus, DIFFICULTY, LANGAGE, SORT_BY, TAGS,
     },
-    utils::{gen_rand_colors, rank_color},
+    utils::{human_size, language_display, rank_color, sanitize_query},
     TERMINAL_REF_SIZE,
 };
 
+// keys of the other modes, the ones of the normal mode and of the list of katas come from their keymaps
 const APP_KEYS_DESC: &str = r#"
-- Actions:
-q: Quit app (normal mode)
-S: Search Kata (normal mode)
-L: Focus List of Katas (normal mode)
-D: Download selected Kata (list of kata)
-
-- Moves:
-Tab:        Go to next field/kata
-Shift+Tab:  Go to previous field/kata
+- Anywhere:
+Ctrl+C:     Quit app
+Ctrl+R:     Retry the failed action
+Ctrl+B:     Broaden a search without results
+
+- Search fields:
+Tab:        Go to next field
+Shift+Tab:  Go to previous field
 Esc:        Exit to normal mode
+
+- Sort by:
+R:  Reverse the direction of the sort
+
+- Download path:
+Tab/Shift+Tab:  Cycle suggestions
+Right/Enter:    Accept suggestion
+
+- Download modal:
+P:  Preview the solution template of the language
 "#;
 
+/// help of the described keys of the keymaps, then of the other modes
+fn keys_help() -> String {
+    let mut help = String::new();
+    for (title, keymap) in [
+        ("Normal mode", NORMAL_KEYMAP),
+        ("List of katas", KATA_LIST_KEYMAP),
+    ] {
+        help.push_str(&format!("\n- {title}:\n"));
+        for (label, _, _, desc, _) in keymap.iter() {
+            if label.len() > 0 {
+                help.push_str(&format!("{label}: {desc}\n"));
+            }
+        }
+    }
+    help.push_str(APP_KEYS_DESC);
+    return help;
+}
+
 // Custom widgets
 pub struct StatefulList<T> {
     pub state: usize,
     pub items: Vec<T>,
+    pub offset: usize, // index of the first item in view
 }
 
 impl<T> StatefulList<T> {
@@ -40,28 +73,116 @@ impl<T> StatefulList<T> {
         StatefulList {
             state: initial_state,
             items,
+            offset: 0,
         }
     }
 
+    /// range of the `in_view` items to display, scrolled (up or down) just enough to keep the selected item visible
+    pub fn visible_range(&mut self, in_view: usize) -> Range<usize> {
+        let in_view = in_view.max(1);
+
+        if self.state < self.offset {
+            self.offset = self.state;
+        } else if self.state >= self.offset + in_view {
+            self.offset = self.state + 1 - in_view;
+        }
+        // the list may have shrunk since the last render
+        if self.offset + in_view > self.items.len() {
+            self.offset = self.items.len().saturating_sub(in_view);
+        }
+
+        self.offset..(self.offset + in_view).min(self.items.len())
+    }
+
     pub fn next(&mut self) {
+        self.step_next(true)
+    }
+
+    pub fn previous(&mut self) {
+        self.step_previous(true)
+    }
+
+    /// next item, going back to the first one after the last one only if `wrap`
+    pub fn step_next(&mut self, wrap: bool) {
         if self.items.len() <= 0 {
             return;
         }
 
         if self.state == self.items.len() - 1 {
-            self.state = 0
+            if wrap {
+                self.state = 0
+            }
         } else {
             self.state += 1;
         }
     }
 
-    pub fn previous(&mut self) {
+    /// item below in a grid of `columns`, the last one from a partial row above it,
+    /// the first row if `wrap` on the last row
+    pub fn step_down(&mut self, columns: usize, wrap: bool) {
+        let columns = columns.max(1);
+        if self.items.len() <= 0 {
+            return;
+        }
+
+        let last = self.items.len() - 1;
+        if self.state + columns <= last {
+            self.state += columns;
+        } else if self.state / columns < last / columns {
+            self.state = last;
+        } else if wrap {
+            self.state %= columns;
+        }
+    }
+
+    /// item above in a grid of `columns`, the last row if `wrap` on the first row
+    pub fn step_up(&mut self, columns: usize, wrap: bool) {
+        let columns = columns.max(1);
+        if self.items.len() <= 0 {
+            return;
+        }
+
+        if self.state >= columns {
+            self.state -= columns;
+        } else if wrap {
+            let last = self.items.len() - 1;
+            self.state = ((last / columns) * columns + self.state).min(last);
+        }
+    }
+
+    /// items in view of a grid of `rows` x `columns`, scrolled by whole rows
+    pub fn visible_grid_range(&mut self, rows: usize, columns: usize) -> Range<usize> {
+        let columns = columns.max(1);
+        let row_range = {
+            let (state, offset) = (self.state / columns, self.offset / columns);
+            let rows = rows.max(1);
+            let row_count = self.items.len().div_ceil(columns);
+            let mut offset = offset;
+            if state < offset {
+                offset = state;
+            } else if state >= offset + rows {
+                offset = state + 1 - rows;
+            }
+            if offset + rows > row_count {
+                offset = row_count.saturating_sub(rows);
+            }
+            offset..offset + rows
+        };
+
+        self.offset = row_range.start * columns;
+        self.offset..(row_range.end * columns).min(self.items.len())
+    }
+
+    /// previous item, going to the last one before the first one only if `wrap`
+    pub fn step_previous(&mut self, wrap: bool) {
         if self.items.len() <= 0 {
             return;
         }
 
         if self.state == 0 {
-            self.state = self.items.len() - 1
+            if wrap {
+                self.state = self.items.len() - 1
+            }
         } else {
             self.state -= 1;
         }
@@ -179,21 +300,59 @@ impl InputWidget {
     }
 }
 
+/// Monochrome theme (https://no-color.org), rendered over the whole frame:
+/// colors are dropped and the emphasis is kept with modifiers instead
+struct StripColors;
+
+impl Widget for StripColors {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        for x in area.left()..area.right() {
+            for y in area.top()..area.bottom() {
+                let cell = buf.get_mut(x, y);
+
+                let mut modifier = cell.modifier;
+                if cell.bg != Color::Reset {
+                    modifier |= Modifier::REVERSED; // tags, badges...
+                }
+                match cell.fg {
+                    Color::Reset | Color::White | Color::Gray => {}
+                    Color::DarkGray => modifier |= Modifier::DIM, // placeholders, inactive borders
+                    _ => modifier |= Modifier::BOLD,              // focused fields, ranks, titles
+                }
+
+                cell.set_style(Style::reset().add_modifier(modifier));
+            }
+        }
+    }
+}
+
 // APP UI
 pub fn ui<B: Backend>(f: &mut Frame<B>, state: &mut CodewarsCLI) {
     let parent_chunk = Layout::default()
         .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(30), Constraint::Percentage(70)].as_ref())
+        .constraints(
+            [
+                Constraint::Percentage(state.split_ratio),
+                Constraint::Percentage(100 - state.split_ratio),
+            ]
+            .as_ref(),
+        )
         .split(f.size());
 
     let search_section = Block::default()
-        .title(Span::styled(
-            "Search Katas",
-            match state.input_mode {
-                InputMode::KataList => Style::default(),
-                _ => Style::default().fg(Color::LightRed),
-            },
-        ))
+        .title(Spans::from(vec![
+            Span::styled(
+                "Search Katas",
+                match state.input_mode {
+                    InputMode::KataList => Style::default(),
+                    _ => Style::default().fg(Color::LightRed),
+                },
+            ),
+            Span::styled(
+                format!(" | Mode: {}", state.mode_label()),
+                Style::default().fg(Color::LightYellow),
+            ),
+        ]))
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
         .border_style(match state.input_mode {
@@ -205,7 +364,16 @@ pub fn ui<B: Backend>(f: &mut Frame<B>, state: &mut CodewarsCLI) {
 
     let list_section_block = Block::default()
         .title(Span::styled(
-            "List of katas",
+            match (&state.collection, &state.author_filter) {
+                _ if state.todo_view => "List of katas (to-do, X to remove)".to_string(),
+                _ if state.user_view.is_some() => format!(
+                    "List of katas (my {})",
+                    state.user_view.map(|kind| kind.label()).unwrap_or_default()
+                ),
+                (Some(collection), _) => format!("List of katas (collection: {collection})"),
+                (None, Some(author)) => format!("List of katas (author: {author})"),
+                (None, None) => "List of katas".to_string(),
+            },
             match state.input_mode {
                 InputMode::KataList => Style::default().fg(Color::LightRed),
                 _ => Style::default(),
@@ -218,16 +386,287 @@ pub fn ui<B: Backend>(f: &mut Frame<B>, state: &mut CodewarsCLI) {
             _ => Style::default(),
         });
     f.render_widget(list_section_block, parent_chunk[1]);
-    if state.download_modal.0 != DownloadModalInput::Disabled {
+    if let Some((files, scroll)) = &state.overwrite_diff {
+        draw_overwrite_diff(f, files, *scroll, parent_chunk[1])
+    } else if let Some(report) = &state.cache_view {
+        draw_cache_view(f, report, parent_chunk[1])
+    } else if let Some((lines, scroll)) = &state.log_view {
+        draw_log_view(f, lines, *scroll, parent_chunk[1])
+    } else if state.download_modal.0 != DownloadModalInput::Disabled {
         draw_download_modal(f, state, parent_chunk[1])
     } else {
         draw_list_section(f, state, parent_chunk[1])
     }
+    draw_message(f, state, parent_chunk[1]);
+    draw_key_hints(f, state, parent_chunk[1]);
+    draw_prompt(f, state, parent_chunk[1]);
+
+    if state.no_color {
+        f.render_widget(StripColors, f.size());
+    }
+}
+
+/// rect of `percent_x`% x `percent_y`% of `area`, centered in it
+pub fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
+    let vertical = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Percentage((100 - percent_y.min(100)) / 2),
+                Constraint::Percentage(percent_y.min(100)),
+                Constraint::Percentage((100 - percent_y.min(100)) / 2),
+            ]
+            .as_ref(),
+        )
+        .split(area);
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(
+            [
+                Constraint::Percentage((100 - percent_x.min(100)) / 2),
+                Constraint::Percentage(percent_x.min(100)),
+                Constraint::Percentage((100 - percent_x.min(100)) / 2),
+            ]
+            .as_ref(),
+        )
+        .split(vertical[1])[1]
+}
+
+/// clear a centered popup of `area` and draw its border, returns the inside of the popup
+fn draw_modal<B: Backend>(
+    f: &mut Frame<B>,
+    title: &str,
+    percent_x: u16,
+    percent_y: u16,
+    area: Rect,
+) -> Rect {
+    let modal_area = centered_rect(percent_x, percent_y, area);
+    let block = Block::default()
+        .title(Span::styled(
+            title.to_string(),
+            Style::default().add_modifier(Modifier::BOLD),
+        ))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+    let inner = block.inner(modal_area);
+
+    f.render_widget(Clear, modal_area);
+    f.render_widget(block, modal_area);
+    inner
+}
+
+/// what a download would change in the files already on disk, in place of the katas
+fn draw_overwrite_diff<B: Backend>(
+    f: &mut Frame<B>,
+    files: &[(String, Vec<DiffLine>)],
+    scroll: usize,
+    area: Rect,
+) {
+    let mut lines = vec![];
+    for (file, diff) in files {
+        lines.push(Spans::from(Span::styled(
+            format!("── {file}"),
+            Style::default().add_modifier(Modifier::BOLD),
+        )));
+        for line in diff {
+            lines.push(match line {
+                DiffLine::Same(l) => Spans::from(Span::styled(
+                    format!("  {l}"),
+                    Style::default().fg(Color::DarkGray),
+                )),
+                DiffLine::Removed(l) => Spans::from(Span::styled(
+                    format!("- {l}"),
+                    Style::default().fg(Color::LightRed),
+                )),
+                DiffLine::Added(l) => Spans::from(Span::styled(
+                    format!("+ {l}"),
+                    Style::default().fg(Color::LightGreen),
+                )),
+            });
+        }
+    }
+
+    let area = draw_modal(f, "Overwrite?", 94, 90, area);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([Constraint::Length(2), Constraint::Min(0)].as_ref())
+        .split(area);
+    f.render_widget(
+        Paragraph::new(Span::styled(
+            "The download would overwrite your changes (- yours, + fresh), y to overwrite, Esc to keep them",
+            Style::default()
+                .fg(Color::LightYellow)
+                .add_modifier(Modifier::BOLD),
+        ))
+        .alignment(Alignment::Center),
+        chunks[0],
+    );
+    f.render_widget(
+        Paragraph::new(lines).scroll((scroll.min(u16::MAX as usize) as u16, 0)),
+        chunks[1],
+    );
 }
 
-fn welcome_text() -> Paragraph<'static> {
-    let colors = [gen_rand_colors(), gen_rand_colors(), gen_rand_colors()];
+/// cache files and their size, in place of the katas
+fn draw_cache_view<B: Backend>(f: &mut Frame<B>, report: &[(String, u64)], area: Rect) {
+    let total = report.iter().map(|(_, size)| size).sum::<u64>();
+    let mut lines = vec![
+        Spans::from(Span::styled(
+            format!("Cache ({})", human_size(total)),
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        Spans::from(""),
+    ];
+    for (name, size) in report {
+        lines.push(Spans::from(format!("{name:<40} {:>10}", human_size(*size))));
+    }
+    lines.push(Spans::from(""));
+    lines.push(Spans::from(Span::styled(
+        "x: clear the cache (settings are kept), Esc: close",
+        Style::default().fg(Color::DarkGray),
+    )));
 
+    let chunks = Layout::default()
+        .margin(2)
+        .constraints([Constraint::Min(0)].as_ref())
+        .split(area);
+    f.render_widget(
+        Paragraph::new(lines).alignment(Alignment::Center),
+        chunks[0],
+    );
+}
+
+/// end of the log file, in place of the katas. `scroll` lines up from the last one
+fn draw_log_view<B: Backend>(f: &mut Frame<B>, lines: &[String], scroll: usize, area: Rect) {
+    let chunks = Layout::default()
+        .margin(2)
+        .constraints([Constraint::Length(2), Constraint::Min(0)].as_ref())
+        .split(area);
+
+    let title = Paragraph::new(Span::styled(
+        match lines.len() {
+            0 => "Logs (empty)".to_string(),
+            count => format!("Logs (last {count} lines)"),
+        },
+        Style::default().add_modifier(Modifier::BOLD),
+    ))
+    .alignment(Alignment::Center);
+    f.render_widget(title, chunks[0]);
+
+    // the bottom line is the last one minus the scroll, the text isn't wrapped to keep the count right
+    let height = chunks[1].height as usize;
+    let bottom = lines.len().saturating_sub(scroll);
+    let top = bottom.saturating_sub(height);
+    let text: Vec<Spans> = lines[top..bottom]
+        .iter()
+        .map(|line| Spans::from(line.to_owned()))
+        .collect();
+    f.render_widget(Paragraph::new(text), chunks[1]);
+}
+
+/// one line input at the bottom of the list section
+fn draw_prompt<B: Backend>(f: &mut Frame<B>, state: &mut CodewarsCLI, area: Rect) {
+    let (kind, input) = match &mut state.prompt {
+        Some(prompt) => prompt,
+        None => return,
+    };
+    if area.height < 7 || area.width < 6 {
+        return;
+    }
+
+    let title = match kind {
+        PromptKind::Export => "Export katas to (.md or .csv), Enter to confirm",
+        PromptKind::Collection => "Collection url or id, Enter to open",
+        PromptKind::ImportSolved => "File of solved kata ids (one per line), Enter to import",
+        PromptKind::TodoNote => "Note of the to-do (e.g: revisit the recursion), Enter to save",
+        PromptKind::ImportFilters => "Shared filters (cw-filters:...), Enter to search",
+    };
+    let prompt_area = Rect {
+        x: area.x + 2,
+        y: area.y + area.height - 5,
+        width: area.width - 4,
+        height: 3,
+    };
+
+    f.render_widget(Clear, prompt_area);
+    f.render_widget(
+        input
+            .basic_render(true)
+            .alignment(Alignment::Left)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .title(title),
+            )
+            .style(Style::default().fg(Color::LightYellow)),
+        prompt_area,
+    );
+}
+
+/// keys of the current mode, on the bottom border of the list section
+fn draw_key_hints<B: Backend>(f: &mut Frame<B>, state: &CodewarsCLI, area: Rect) {
+    if area.height < 3 || area.width < 6 {
+        return;
+    }
+
+    let mut spans = vec![];
+    for (i, (key, action)) in state.key_hints().into_iter().enumerate() {
+        if i > 0 {
+            spans.push(Span::styled(" · ", Style::default().fg(Color::DarkGray)));
+        }
+        spans.push(Span::styled(key, Style::default().fg(Color::LightYellow)));
+        spans.push(Span::raw(format!(" {action}")));
+    }
+
+    let hints_area = Rect {
+        x: area.x + 2,
+        y: area.y + area.height - 1,
+        width: area.width - 4,
+        height: 1,
+    };
+    f.render_widget(
+        Paragraph::new(Spans::from(spans)).alignment(Alignment::Center),
+        hints_area,
+    );
+}
+
+/// render the user message on the bottom margin of the list section
+fn draw_message<B: Backend>(f: &mut Frame<B>, state: &CodewarsCLI, area: Rect) {
+    let (kind, message) = match &state.message {
+        Some(m) => m,
+        None => return,
+    };
+    if area.height < 4 || area.width < 6 {
+        return;
+    }
+
+    let color = match kind {
+        MessageKind::Info => Color::LightCyan,
+        MessageKind::Success => Color::LightGreen,
+        MessageKind::Error => Color::LightRed,
+    };
+    let message = match (kind, &state.retry) {
+        (MessageKind::Error, Some(_)) => format!("{message} (Ctrl+R to retry)"),
+        _ => message.to_owned(),
+    };
+    let line = Paragraph::new(Span::styled(
+        message,
+        Style::default().fg(color).add_modifier(Modifier::BOLD),
+    ))
+    .alignment(Alignment::Center);
+
+    let message_area = Rect {
+        x: area.x + 2,
+        y: area.y + area.height - 2,
+        width: area.width - 4,
+        height: 1,
+    };
+    f.render_widget(line, message_area);
+}
+
+fn welcome_text(colors: [Color; 3]) -> Paragraph<'static> {
     let text = vec![
         Spans::from(vec![
             Span::styled(
@@ -246,14 +685,29 @@ fn welcome_text() -> Paragraph<'static> {
             ),
         ]),
         Spans::from("A tool to download katas locally"),
-        Spans::from(APP_KEYS_DESC),
+        Spans::from(keys_help()),
     ];
 
     return Paragraph::new(text).alignment(Alignment::Center);
 }
 
+/// rows of a dropdown of `items` (with its borders) in `available` rows, at least one item is shown
+pub fn dropdown_height(items: usize, available: u16) -> u16 {
+    let needed = (items.min(u16::MAX as usize - 2) as u16).max(1) + 2;
+    needed.min(available.max(3))
+}
+
+/// `items_in_views`: exact number of visible items, by default as many as a full height section holds, never 0
+pub fn dropdown_items_in_view(terminal_size: &(u16, u16), items_in_views: Option<u16>) -> usize {
+    return match items_in_views {
+        Some(count) => count.max(1) as usize,
+        // for a terminal with 34 rows we can display 26 items of the list
+        None => ((26 * terminal_size.1 as u32) / TERMINAL_REF_SIZE.1 as u32).max(1) as usize,
+    };
+}
+
 fn dropdown(
-    dropdown_info: &StatefulList<(String, usize)>,
+    dropdown_info: &mut StatefulList<(String, usize)>,
     input_mode: &InputMode,
     terminal_size: &(u16, u16),
     items_in_views: Option<u16>,
@@ -269,9 +723,15 @@ fn dropdown(
     let items = dropdown_info
         .items
         .iter()
-        .map(|(content, i)| {
-            let is_active = i == &dropdown_info.state;
+        .enumerate()
+        .map(|(i, (content, _))| {
+            let is_active = i == dropdown_info.state;
 
+            // the download modal lists codewars slugs, the names of the filter are kept as is
+            let content = match input_mode {
+                InputMode::Langage => language_display(content),
+                _ => content,
+            };
             ListItem::new(Spans::from(Span::styled(
                 if is_active {
                     ">> ".to_string() + content
@@ -290,32 +750,30 @@ fn dropdown(
         })
         .collect::<Vec<ListItem>>();
 
-    let wanted_item_in_view: u16 = match items_in_views {
-        Some(iivr) => iivr,
-        None => 26,
-    }; // for a terminal with 34 rows we can display 26 items of the list
-    let items_in_view =
-        (((wanted_item_in_view * terminal_size.1) / TERMINAL_REF_SIZE.1) - 1) as usize;
-    let items_ranges = if dropdown_info.state > items_in_view {
-        (dropdown_info.state - items_in_view)..=dropdown_info.state
-    } else {
-        0..=items.len() - 1
-    };
+    let items_ranges =
+        dropdown_info.visible_range(dropdown_items_in_view(terminal_size, items_in_views));
 
+    // the active item is highlighted by hand above, as the list is rendered without a ListState
     return List::new(items[items_ranges].to_owned())
         .block(Block::default().title(title).borders(Borders::ALL))
-        .style(Style::default().fg(Color::White))
-        .highlight_style(
-            Style::default()
-                .bg(Color::LightGreen)
-                .add_modifier(Modifier::BOLD),
-        )
-        .highlight_symbol(">> ");
+        .style(Style::default().fg(Color::White));
 }
 
 fn draw_search_section<B: Backend>(f: &mut Frame<B>, state: &mut CodewarsCLI, area: Rect) {
     let contraints = if state.field_dropdown.0 {
         vec![Constraint::Length(2), Constraint::Min(4)]
+    } else if state.help_collapsed {
+        // the fields right under the help line, the free space goes below them
+        vec![
+            Constraint::Length(2),
+            Constraint::Length(1),
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Min(0),
+        ]
     } else {
         vec![
             Constraint::Length(2),
@@ -334,22 +792,57 @@ fn draw_search_section<B: Backend>(f: &mut Frame<B>, state: &mut CodewarsCLI, ar
         .constraints(contraints.as_ref())
         .split(area);
 
-    f.render_widget(welcome_text(), chunks[0]);
+    f.render_widget(welcome_text(state.banner_colors()), chunks[0]);
 
     if state.field_dropdown.0 {
+        let dropdown_area = if state.is_dropdown_searchable() {
+            let dropdown_chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(3), Constraint::Min(1)].as_ref())
+                .split(chunks[1]);
+
+            let search_line = state
+                .dropdown_filter
+                .basic_render(true)
+                .alignment(Alignment::Left)
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_type(BorderType::Rounded)
+                        .title(if state.dropdown_sorted {
+                            "Type to search · Tab: built-in order"
+                        } else {
+                            "Type to search · Tab: A-Z order"
+                        }),
+                )
+                .style(Style::default().fg(Color::LightYellow));
+            f.render_widget(search_line, dropdown_chunks[0]);
+            dropdown_chunks[1]
+        } else {
+            chunks[1]
+        };
+
         f.render_widget(
             dropdown(
-                &state.field_dropdown.1,
+                &mut state.field_dropdown.1,
                 &state.input_mode,
                 &state.terminal_size,
                 None,
             ),
-            chunks[1],
+            dropdown_area,
         );
+        state.field_areas.clear(); // the dropdown covers the fields
         return;
     }
 
-    let help = Paragraph::new(APP_KEYS_DESC);
+    let help = if state.help_collapsed {
+        Paragraph::new(Span::styled(
+            "h: show the keys",
+            Style::default().fg(Color::DarkGray),
+        ))
+    } else {
+        Paragraph::new(keys_help())
+    };
     f.render_widget(help, chunks[1]);
 
     let search = state
@@ -360,28 +853,50 @@ fn draw_search_section<B: Backend>(f: &mut Frame<B>, state: &mut CodewarsCLI, ar
             Block::default()
                 .borders(Borders::ALL)
                 .border_type(BorderType::Rounded)
-                .title("Search Kata"),
+                .title(field_title(
+                    if sanitize_query(&state.search_field.value, SEARCH_MAX_LEN).1 {
+                        format!("Search Kata (truncated to {SEARCH_MAX_LEN} chars)")
+                    } else {
+                        "Search Kata".to_string()
+                    },
+                    state.field_hint(&InputMode::Search),
+                )),
         )
         .style(match state.input_mode {
             InputMode::Search => Style::default().fg(Color::LightYellow),
             _ => Style::default(),
         });
     f.render_widget(search, chunks[2]);
+    state.field_areas = vec![
+        (InputMode::Search, chunks[2]),
+        (InputMode::SortBy, chunks[3]),
+        (InputMode::Langage, chunks[4]),
+        (InputMode::Difficulty, chunks[5]),
+        (InputMode::Tags, chunks[6]),
+    ];
 
-    let sortby = Paragraph::new(SORT_BY[state.sortby_field].to_owned())
-        .alignment(Alignment::Center)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .border_type(BorderType::Rounded)
-                .title("Sort By"),
-        )
-        .style(match state.input_mode {
-            InputMode::SortBy => Style::default().fg(Color::LightYellow),
-            _ => Style::default(),
-        });
+    let sortby = Paragraph::new(match state.sort_order() {
+        Some((_, true)) => format!("{} ↓", SORT_BY[state.sortby_field]),
+        Some((_, false)) => format!("{} ↑", SORT_BY[state.sortby_field]),
+        None => SORT_BY[state.sortby_field].to_owned(),
+    })
+    .alignment(Alignment::Center)
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .title(field_title(
+                "Sort By".to_string(),
+                state.field_hint(&InputMode::SortBy),
+            )),
+    )
+    .style(match state.input_mode {
+        InputMode::SortBy => Style::default().fg(Color::LightYellow),
+        _ => Style::default(),
+    });
     f.render_widget(sortby, chunks[3]);
 
+    let my_languages = state.my_languages_preset();
     let language = Paragraph::new(if state.langage_field == 0 {
         Span::styled(
             LANGAGE[state.langage_field].to_owned(),
@@ -389,6 +904,12 @@ fn draw_search_section<B: Backend>(f: &mut Frame<B>, state: &mut CodewarsCLI, ar
                 .fg(Color::DarkGray)
                 .add_modifier(Modifier::ITALIC),
         )
+    } else if let Some(languages) = &my_languages {
+        Span::from(format!(
+            "{} ({})",
+            LANGAGE[state.langage_field],
+            languages.join(", ")
+        ))
     } else {
         Span::from(LANGAGE[state.langage_field].to_owned())
     })
@@ -397,7 +918,13 @@ fn draw_search_section<B: Backend>(f: &mut Frame<B>, state: &mut CodewarsCLI, ar
         Block::default()
             .borders(Borders::ALL)
             .border_type(BorderType::Rounded)
-            .title("Language"),
+            .title(field_title(
+                match my_languages {
+                    Some(_) => "Language (local preset)".to_string(),
+                    None => "Language".to_string(),
+                },
+                state.field_hint(&InputMode::Langage),
+            )),
     )
     .style(match state.input_mode {
         InputMode::Langage => Style::default().fg(Color::LightYellow),
@@ -420,7 +947,10 @@ fn draw_search_section<B: Backend>(f: &mut Frame<B>, state: &mut CodewarsCLI, ar
         Block::default()
             .borders(Borders::ALL)
             .border_type(BorderType::Rounded)
-            .title("Difficulty"),
+            .title(field_title(
+                "Difficulty".to_string(),
+                state.field_hint(&InputMode::Difficulty),
+            )),
     )
     .style(match state.input_mode {
         InputMode::Difficulty => Style::default().fg(Color::LightYellow),
@@ -443,7 +973,10 @@ fn draw_search_section<B: Backend>(f: &mut Frame<B>, state: &mut CodewarsCLI, ar
         Block::default()
             .borders(Borders::ALL)
             .border_type(BorderType::Rounded)
-            .title("Tags"),
+            .title(field_title(
+                "Tags".to_string(),
+                state.field_hint(&InputMode::Tags),
+            )),
     )
     .style(match state.input_mode {
         InputMode::Tags => Style::default().fg(Color::LightYellow),
@@ -452,46 +985,142 @@ fn draw_search_section<B: Backend>(f: &mut Frame<B>, state: &mut CodewarsCLI, ar
     f.render_widget(tags, chunks[6]);
 }
 
+/// title of a search field, with its hint if any
+fn field_title(title: String, hint: Option<&str>) -> Spans<'static> {
+    let mut spans = vec![Span::from(title)];
+    if let Some(hint) = hint {
+        spans.push(Span::styled(
+            format!(" · {hint}"),
+            Style::default()
+                .fg(Color::LightRed)
+                .add_modifier(Modifier::ITALIC),
+        ));
+    }
+    Spans::from(spans)
+}
+
 fn draw_list_section<B: Backend>(f: &mut Frame<B>, state: &mut CodewarsCLI, area: Rect) {
-    if state.search_result.items.len() <= 0 {
+    if state.search_status == SearchStatus::Loading || state.search_result.items.len() <= 0 {
+        draw_empty_list(f, state, area);
         return;
     }
 
+    // as many 5 rows cards as the section can hold, plus the position line
+    const KATA_HEIGHT: u16 = 5;
+    // the cards get too cramped to read below this width
+    const KATA_MIN_WIDTH: u16 = 50;
+    let rows_in_view = (area.height.saturating_sub(4 + 1) / KATA_HEIGHT).max(1) as usize;
+    state.grid_columns = state.list_columns.clamp(
+        1,
+        (area.width.saturating_sub(4) / KATA_MIN_WIDTH).max(1) as usize,
+    );
+    let items_ranges = state
+        .search_result
+        .visible_grid_range(rows_in_view, state.grid_columns);
+    let rows = items_ranges.len().div_ceil(state.grid_columns);
+
+    let mut constraints = vec![Constraint::Length(KATA_HEIGHT); rows];
+    constraints.push(Constraint::Min(0));
+    constraints.push(Constraint::Length(1));
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .margin(2)
-        .constraints(
-            [
-                Constraint::Min(5),
-                Constraint::Min(5),
-                Constraint::Min(5),
-                Constraint::Min(5),
-                Constraint::Min(5),
-                Constraint::Min(5),
-            ]
-            .as_ref(),
-        )
+        .constraints(constraints.as_slice())
         .split(area);
 
-    const ITEMS_IN_VIEW_REF: usize = 6 - 1; // for a terminal with 34 rows we can display  items of the list
-    let items_ranges = if state.search_result.items.len() - 1 <= ITEMS_IN_VIEW_REF {
-        0..=(state.search_result.items.len() - 1)
-    } else if state.search_result.state > ITEMS_IN_VIEW_REF {
-        (state.search_result.state - ITEMS_IN_VIEW_REF)..=state.search_result.state
-    } else {
-        0..=ITEMS_IN_VIEW_REF
-    };
+    let position = Paragraph::new(Span::styled(
+        format!(
+            "showing {}–{} of {}",
+            items_ranges.start + 1,
+            items_ranges.end,
+            state.search_result.items.len()
+        ),
+        Style::default().fg(Color::DarkGray),
+    ))
+    .alignment(Alignment::Right);
+    f.render_widget(position, chunks[chunks.len() - 1]);
 
+    let columns = state.grid_columns;
+    let cells = chunks[..rows]
+        .iter()
+        .flat_map(|row| {
+            Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints(vec![Constraint::Ratio(1, columns as u32); columns])
+                .split(*row)
+        })
+        .collect::<Vec<Rect>>();
     for (i, (kata, kata_idx)) in (&state.search_result.items[items_ranges])
         .iter()
         .enumerate()
     {
         let is_active = *kata_idx == state.search_result.state;
-        f.render_widget(draw_kata(kata, is_active), chunks[i]);
+        let solved = state.solved.contains(&kata.id);
+        let queued = state.download_queue.iter().any(|k| k.id == kata.id);
+        let note = state.todo_note(&kata.id);
+        f.render_widget(
+            draw_kata(
+                kata,
+                is_active,
+                solved,
+                queued,
+                note,
+                state.show_ids,
+                state.no_color,
+            ),
+            cells[i],
+        );
     }
 }
 
-fn draw_kata(kata: &KataAPI, is_active: bool) -> Paragraph<'static> {
+/// centered hint in place of the katas, depending on why there is none
+fn draw_empty_list<B: Backend>(f: &mut Frame<B>, state: &CodewarsCLI, area: Rect) {
+    let hint = match state.search_status {
+        SearchStatus::NeverSearched => "Press S then Enter to search katas",
+        SearchStatus::Loading => "⏳ Loading katas...",
+        SearchStatus::Empty => "No results — press Ctrl+B to broaden the filters",
+        SearchStatus::Failed if state.retry.is_some() => {
+            "Search failed — check your connection and press Ctrl+R to retry"
+        }
+        // the retry is dropped by the next key
+        SearchStatus::Failed => "Search failed — check your connection and search again",
+        SearchStatus::LoginRequired => {
+            "This search requires login — set your codewars token (CODEWARS_TOKEN or api_token)"
+        }
+        SearchStatus::Results => return,
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Percentage(45),
+                Constraint::Length(1),
+                Constraint::Min(0),
+            ]
+            .as_ref(),
+        )
+        .split(area);
+
+    let text = Paragraph::new(Span::styled(
+        hint,
+        Style::default()
+            .fg(Color::DarkGray)
+            .add_modifier(Modifier::ITALIC),
+    ))
+    .alignment(Alignment::Center);
+    f.render_widget(text, chunks[1]);
+}
+
+fn draw_kata(
+    kata: &KataAPI,
+    is_active: bool,
+    solved: bool,
+    queued: bool,
+    note: Option<&str>, // of its to-do
+    show_id: bool,
+    no_color: bool,
+) -> Paragraph<'static> {
     const FG_HEAD: tui::style::Color = Color::Rgb(104, 175, 49);
 
     let mut tags: Vec<Span> = vec![Span::styled(
@@ -534,6 +1163,17 @@ fn draw_kata(kata: &KataAPI, is_active: bool) -> Paragraph<'static> {
                     .fg(Color::LightCyan),
             ),
             Span::raw(kata.createdBy.username.to_owned()),
+            // only known once the kata was fetched from the api
+            if kata.category.len() > 0 {
+                Span::styled(
+                    format!(" | {}", kata.category),
+                    Style::default()
+                        .fg(Color::LightMagenta)
+                        .add_modifier(Modifier::ITALIC),
+                )
+            } else {
+                Span::raw("")
+            },
         ]),
         Spans::from(tags),
         Spans::from(languages),
@@ -547,16 +1187,68 @@ fn draw_kata(kata: &KataAPI, is_active: bool) -> Paragraph<'static> {
                         kata.name.to_owned(),
                         Style::default().add_modifier(Modifier::BOLD).fg(FG_HEAD),
                     ),
+                    if show_id {
+                        Span::styled(
+                            format!(" {}", kata.id),
+                            Style::default().fg(Color::DarkGray),
+                        )
+                    } else {
+                        Span::raw("")
+                    },
                     Span::raw(" - "),
+                    if kata.beta {
+                        Span::styled(
+                            "BETA ",
+                            Style::default()
+                                .add_modifier(Modifier::BOLD)
+                                .fg(Color::Black)
+                                .bg(Color::LightYellow),
+                        )
+                    } else {
+                        Span::raw("")
+                    },
                     Span::styled(
                         kata.rank.name.to_owned(),
                         Style::default()
                             .add_modifier(Modifier::BOLD)
                             .fg(rank_color(kata.rank.name.as_str(), Color::White)),
                     ),
+                    if queued {
+                        Span::styled(
+                            " [queued]",
+                            Style::default()
+                                .add_modifier(Modifier::BOLD)
+                                .fg(Color::LightYellow),
+                        )
+                    } else {
+                        Span::raw("")
+                    },
+                    if solved {
+                        Span::styled(
+                            " ✓ solved",
+                            Style::default()
+                                .add_modifier(Modifier::BOLD)
+                                .fg(Color::LightGreen),
+                        )
+                    } else {
+                        Span::raw("")
+                    },
+                    match note {
+                        Some("") => Span::styled(" [to-do]", Style::default().fg(Color::LightCyan)),
+                        Some(note) => Span::styled(
+                            format!(" [to-do: {note}]"),
+                            Style::default().fg(Color::LightCyan),
+                        ),
+                        None => Span::raw(""),
+                    },
                 ]))
                 .borders(Borders::ALL)
-                .border_type(BorderType::Rounded)
+                // without colors, the active kata can only be told apart by its border
+                .border_type(if is_active && no_color {
+                    BorderType::Thick
+                } else {
+                    BorderType::Rounded
+                })
                 .border_style(if is_active {
                     Style::default().fg(rank_color(kata.rank.name.as_str(), Color::LightGreen))
                 } else {
@@ -569,34 +1261,22 @@ fn draw_kata(kata: &KataAPI, is_active: bool) -> Paragraph<'static> {
 }
 
 fn draw_download_modal<B: Backend>(f: &mut Frame<B>, state: &mut CodewarsCLI, area: Rect) {
-    const ITEM_IN_VIEW: u16 = 18;
-    let compute_percent = |no_items: usize| -> u16 {
-        ((((no_items as f64 - 1.0) * 10.0) / 3.0) + 10.0).ceil() as u16
-
-        // why all these fancy number? Just used regression to find a mathematical law
-        // -> affine way
-        // (((no_items as f64) + 1.80519480519481) / 0.298961038961039).ceil() as u16
-        // -> polynomial way, much more precise on the right interval (from 0% to 65%)
-        // let a: f64 = 0.00145854145854146;
-        // let b: f64 = 0.1993006993007;
-        // let c: f64 = -0.72527472527431 - no_items as f64;
-        // let delta = b.powi(2) - 4.0 * a * c;
-        // ((-b + delta.sqrt()) / (2.0 * a)).round() as u16
-    };
+    let area = draw_modal(f, "Download", 94, 94, area);
+    // header and the path, editor and submit fields, the open dropdown takes the rest if it needs it
+    const FIXED_HEIGHT: u16 = 1 + 3 * 3;
+    let dropdown_rows = dropdown_height(
+        state.download_langage.1.items.len(),
+        area.height.saturating_sub(2 + FIXED_HEIGHT), // 1 of margin on both sides
+    );
 
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .margin(2)
+        .margin(1)
         .constraints(
             [
                 Constraint::Length(1),
                 if state.download_langage.0 {
-                    let percent = if state.download_langage.1.items.len() <= ITEM_IN_VIEW as usize {
-                        compute_percent(state.download_langage.1.items.len())
-                    } else {
-                        65
-                    };
-                    Constraint::Percentage(percent)
+                    Constraint::Length(dropdown_rows)
                 } else {
                     Constraint::Length(3)
                 },
@@ -609,37 +1289,63 @@ fn draw_download_modal<B: Backend>(f: &mut Frame<B>, state: &mut CodewarsCLI, ar
         )
         .split(area);
 
-    let header = Paragraph::new(
-        state.search_result.items[state.download_modal.1]
-            .0
-            .name
-            .to_owned(),
-    )
+    // the list can change while the modal is open
+    let kata_name = match state.search_result.items.get(state.download_modal.1) {
+        _ if state.queue_modal => format!("{} queued katas", state.download_queue.len()),
+        Some((kata, _)) if kata.beta => format!("{} (beta: its tests may still change)", kata.name),
+        Some((kata, _)) => kata.name.to_owned(),
+        None => return,
+    };
+    let header = Paragraph::new(match &state.download_warning {
+        Some(warning) => Spans::from(vec![
+            Span::raw(kata_name),
+            Span::styled(
+                format!(" — {warning}"),
+                Style::default()
+                    .fg(Color::LightRed)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ]),
+        None => Spans::from(kata_name),
+    })
     .alignment(Alignment::Center);
     f.render_widget(header, chunks[0]);
 
     if state.download_langage.0 {
         f.render_widget(
             dropdown(
-                &state.download_langage.1,
+                &mut state.download_langage.1,
                 &InputMode::Langage,
                 &state.terminal_size,
-                Some(ITEM_IN_VIEW),
+                Some(dropdown_rows.saturating_sub(2)),
             ),
             chunks[1],
         );
     } else {
+        // no language until the api ones are fetched, for a to-do saved offline
         let language = Paragraph::new(
-            state.download_langage.1.items[state.download_langage.1.state]
-                .0
-                .to_owned(),
+            match state
+                .download_langage
+                .1
+                .items
+                .get(state.download_langage.1.state)
+            {
+                Some((language, _)) => language_display(language).to_string(),
+                None => String::new(),
+            },
         )
         .alignment(Alignment::Center)
         .block(
             Block::default()
                 .borders(Borders::ALL)
                 .border_type(BorderType::Rounded)
-                .title("Kata Langage"),
+                .title(if state.languages_loading {
+                    "Kata Langage (loading languages...)"
+                } else if state.download_langage_locked {
+                    "Kata Langage (locked to the search filter, u to unlock)"
+                } else {
+                    "Kata Langage"
+                }),
         )
         .style(match state.download_modal.0 {
             DownloadModalInput::Langage => Style::default().fg(Color::LightYellow),
@@ -648,6 +1354,14 @@ fn draw_download_modal<B: Backend>(f: &mut Frame<B>, state: &mut CodewarsCLI, ar
         f.render_widget(language, chunks[1]);
     }
 
+    // the kata gets its own folder in the typed path
+    let download_path_title = match state.search_result.items.get(state.download_modal.1) {
+        Some((kata, _)) if !state.queue_modal => format!(
+            "Download Path (→ {})",
+            kata.kata_dir(&state.download_path.value)
+        ),
+        _ => "Download Path (→ one folder per kata)".to_string(),
+    };
     let path = state
         .download_path
         .basic_render(state.download_modal.0 == DownloadModalInput::Path)
@@ -656,7 +1370,7 @@ fn draw_download_modal<B: Backend>(f: &mut Frame<B>, state: &mut CodewarsCLI, ar
             Block::default()
                 .borders(Borders::ALL)
                 .border_type(BorderType::Rounded)
-                .title("Download Path"),
+                .title(download_path_title),
         )
         .style(match state.download_modal.0 {
             DownloadModalInput::Path => Style::default().fg(Color::LightYellow),
@@ -680,16 +1394,53 @@ fn draw_download_modal<B: Backend>(f: &mut Frame<B>, state: &mut CodewarsCLI, ar
         });
     f.render_widget(editor, chunks[3]);
 
-    let submit = Paragraph::new("Download ✅")
-        .alignment(Alignment::Center)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .border_type(BorderType::Rounded),
-        )
-        .style(match state.download_modal.0 {
-            DownloadModalInput::Submit => Style::default().fg(Color::LightGreen),
-            _ => Style::default(),
-        });
+    let submit = Paragraph::new(match &state.download_progress {
+        Some((current, total, language)) => {
+            format!("Downloading {language} {current}/{total}...")
+        }
+        None => format!(
+            "Download ✅ (instruction as {}, f to change)",
+            state.instruction_format.filename()
+        ),
+    })
+    .alignment(Alignment::Center)
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded),
+    )
+    .style(match state.download_modal.0 {
+        DownloadModalInput::Submit => Style::default().fg(Color::LightGreen),
+        _ => Style::default(),
+    });
     f.render_widget(submit, chunks[4]);
+
+    if state.template_preview && !state.download_langage.0 {
+        draw_template_preview(f, state, chunks[5]);
+    }
+}
+
+/// first lines of the solution template of the download modal language, what fits in `area`
+fn draw_template_preview<B: Backend>(f: &mut Frame<B>, state: &CodewarsCLI, area: Rect) {
+    let key = match state.template_preview_key() {
+        Some(key) => key,
+        None => return,
+    };
+    let lines: Vec<Spans> = match state.template_previews.get(&key) {
+        Some(template) => template
+            .iter()
+            .take(area.height.saturating_sub(2) as usize)
+            .map(|line| Spans::from(line.to_owned()))
+            .collect(),
+        None if state.template_preview_loading => vec![Spans::from("Loading...")],
+        None => vec![Spans::from("p to load this language template")],
+    };
+
+    let preview = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .title(format!("Solution preview ({})", language_display(&key.1))),
+    );
+    f.render_widget(preview, area);
 }
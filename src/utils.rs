@@ -1,15 +1,37 @@
-use std::fs::{self, OpenOptions};
+use std::fs::OpenOptions;
 use std::io::prelude::*;
-use std::{error::Error, fs::File, path::Path, process::Command};
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    fmt,
+    fs::{self, File},
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    sync::RwLock,
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
 
-use reqwest::Url;
-use scraper::element_ref::Text;
+use reqwest::{StatusCode, Url};
+use scraper::{element_ref::Text, Html, Selector};
 use tui::style::Color;
 
-use rand::Rng;
+use arboard::Clipboard;
+use pulldown_cmark::{html, Event, Parser, Tag};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use urlencoding::encode;
 use users::get_current_username;
 
-use crate::types::KataAPI;
+use crate::{
+    paths,
+    types::{APIRank, APIUserKatas, DiffLine, KataAPI, UserKatas, LANGAGE},
+};
+
+const CODEWARS_URL: &str = "https://www.codewars.com";
+// full katas saved for offline reading, in the cache dir
+const OFFLINE_KATAS_FILE: &str = "offline_katas.json";
+// an opener that failed has exited by then (not installed, no handler...)
+const OPENER_CHECK_DELAY: Duration = Duration::from_millis(300);
 
 /// generate a random integer between a and b included
 pub fn rand_int(a: isize, b: isize) -> isize {
@@ -25,6 +47,44 @@ pub fn gen_rand_colors() -> Color {
     )
 }
 
+/// where the banner colors come from, random by default, reproducible for the tests
+pub trait ColorSource {
+    fn next_color(&mut self) -> Color;
+}
+
+/// a new random color each time
+pub struct RandomColors;
+
+impl ColorSource for RandomColors {
+    fn next_color(&mut self) -> Color {
+        gen_rand_colors()
+    }
+}
+
+/// the same sequence of colors for the same seed
+pub struct SeededColors(StdRng);
+
+impl SeededColors {
+    pub fn new(seed: u64) -> Self {
+        SeededColors(StdRng::seed_from_u64(seed))
+    }
+}
+
+impl ColorSource for SeededColors {
+    fn next_color(&mut self) -> Color {
+        Color::Rgb(self.0.gen(), self.0.gen(), self.0.gen())
+    }
+}
+
+/// always the same color
+pub struct FixedColors(pub Color);
+
+impl ColorSource for FixedColors {
+    fn next_color(&mut self) -> Color {
+        self.0
+    }
+}
+
 pub fn rank_color(rank: &str, default: Color) -> Color {
     match rank {
         "1 kyu" | "2 kyu" => Color::Rgb(134, 108, 199),
@@ -47,17 +107,232 @@ pub fn trim_specials_chars(string: &str) -> String {
     return out;
 }
 
+/// search query as sent to codewars: no control chars, trimmed and at most `max_len` chars.
+/// The bool is true when the query was truncated
+pub fn sanitize_query(query: &str, max_len: usize) -> (String, bool) {
+    let cleaned = query
+        .chars()
+        .filter(|c| !c.is_control())
+        .collect::<String>();
+    let cleaned = cleaned.trim();
+
+    let truncated = cleaned.chars().count() > max_len;
+    let query = cleaned.chars().take(max_len).collect::<String>();
+    (query.trim_end().to_string(), truncated)
+}
+
+/// line by line diff from `old` to `new` (longest common subsequence, fine for kata sized files)
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old = old.lines().collect::<Vec<&str>>();
+    let new = new.lines().collect::<Vec<&str>>();
+
+    // lcs[i][j]: length of the longest common subsequence of old[i..] and new[j..]
+    let mut lcs = vec![vec![0; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut diff = vec![];
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            diff.push(DiffLine::Same(old[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            diff.push(DiffLine::Removed(old[i].to_string()));
+            i += 1;
+        } else {
+            diff.push(DiffLine::Added(new[j].to_string()));
+            j += 1;
+        }
+    }
+    diff.extend(old[i..].iter().map(|l| DiffLine::Removed(l.to_string())));
+    diff.extend(new[j..].iter().map(|l| DiffLine::Added(l.to_string())));
+    return diff;
+}
+
+/// clean the lines of a scraped code editor: CodeMirror renders empty lines as a zero width space,
+/// indents with non-breaking spaces and its inner text can end with a line break (doubling the blank lines)
+pub fn normalize_code_lines(lines: Vec<String>) -> Vec<String> {
+    let mut normalized = lines
+        .iter()
+        .flat_map(|line| {
+            let line = line
+                .replace("\r\n", "\n")
+                .replace('\r', "\n")
+                .replace('\u{200b}', "")
+                .replace('\u{a0}', " ");
+            let line = line.strip_suffix('\n').unwrap_or(&line).to_string();
+            line.split('\n')
+                .map(|part| part.trim_end().to_string())
+                .collect::<Vec<String>>()
+        })
+        .skip_while(|line| line.len() <= 0)
+        .collect::<Vec<String>>();
+
+    while normalized.last().is_some_and(|line| line.len() <= 0) {
+        normalized.pop();
+    }
+    return normalized;
+}
+
+/// codewars kata ids are 24 hex chars (mongodb object ids), e.g: "5277c8a221e209d3f6000b56"
+pub fn is_kata_id(query: &str) -> bool {
+    query.len() == 24 && query.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// commands that can open `target` (url, file or folder) with the default app of the platform,
+/// in the order they are tried
+fn platform_openers(target: &str, browsers: &str) -> Vec<(String, Vec<String>)> {
+    if cfg!(target_os = "windows") {
+        // start is a builtin of cmd, its first quoted arg is the window title
+        return vec![(
+            "cmd".to_string(),
+            vec![
+                "/C".to_string(),
+                "start".to_string(),
+                String::new(),
+                target.to_string(),
+            ],
+        )];
+    }
+    if cfg!(target_os = "macos") {
+        return vec![("open".to_string(), vec![target.to_string()])];
+    }
+
+    let mut openers = vec![("xdg-open".to_string(), vec![target.to_string()])];
+    // `browsers` ($BROWSER) is a ':' separated list of commands, `%s` is the url (appended when missing)
+    for browser in browsers.split(':') {
+        let mut args = browser.split_whitespace().map(|arg| arg.to_string());
+        let program = match args.next() {
+            Some(program) => program,
+            None => continue,
+        };
+        let mut args = args.collect::<Vec<String>>();
+        if args.iter().any(|arg| arg.contains("%s")) {
+            args = args.iter().map(|arg| arg.replace("%s", target)).collect();
+        } else {
+            args.push(target.to_string());
+        }
+        openers.push((program, args));
+    }
+    openers.push((
+        "gio".to_string(),
+        vec!["open".to_string(), target.to_string()],
+    ));
+    return openers;
+}
+
+/// the child keeps its stderr, a browser would be killed writing to a closed pipe
+fn drain_stderr(child: &mut Child) {
+    if let Some(mut stderr) = child.stderr.take() {
+        thread::spawn(move || std::io::copy(&mut stderr, &mut std::io::sink()));
+    }
+}
+
+/// run the first of `openers` that is installed, without waiting for it more than OPENER_CHECK_DELAY:
+/// a terminal browser from $BROWSER keeps running until the user quits it
+fn run_opener(openers: Vec<(String, Vec<String>)>) -> Result<(), String> {
+    let mut failure = None;
+    for (program, args) in &openers {
+        let mut child = match Command::new(program)
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(_) => continue, // not installed, try the next one
+        };
+
+        let started = Instant::now();
+        let status = loop {
+            match child.try_wait() {
+                Ok(Some(status)) => break Some(status),
+                Ok(None) if started.elapsed() < OPENER_CHECK_DELAY => {
+                    thread::sleep(Duration::from_millis(20))
+                }
+                _ => break None, // still running: it opened
+            }
+        };
+        match status {
+            Some(status) if !status.success() => {
+                let mut stderr = String::new();
+                if let Some(mut out) = child.stderr.take() {
+                    out.read_to_string(&mut stderr).unwrap_or_default();
+                }
+                let stderr = stderr.trim();
+                failure = Some(match stderr.len() {
+                    0 => format!("{program} failed ({status})"),
+                    _ => format!("{program} failed: {stderr}"),
+                });
+            }
+            _ => {
+                drain_stderr(&mut child);
+                return Ok(());
+            }
+        }
+    }
+
+    return Err(failure.unwrap_or_else(|| {
+        let tried = openers
+            .iter()
+            .map(|(program, _)| program.as_str())
+            .collect::<Vec<&str>>();
+        format!("no {} found", tried.join(", "))
+    }));
+}
+
 pub fn open_url(url: &str) -> Result<(), String> {
-    let cmd_res = if cfg!(target_os = "windows") {
-        Command::new("start").arg(url).output()
-    } else {
-        Command::new("xdg-open").arg(url).output()
-    };
+    run_opener(platform_openers(
+        url,
+        &std::env::var("BROWSER").unwrap_or_default(),
+    ))
+}
 
-    return match cmd_res {
-        Ok(_) => Ok(()),
-        Err(err) => Err(err.to_string()),
+/// open the folder `path` in the file manager
+pub fn reveal_in_file_manager(path: &str) -> Result<(), String> {
+    if !Path::new(path).is_dir() {
+        return Err(format!("{path} doesn't exist"));
+    }
+    run_opener(platform_openers(path, ""))
+}
+
+/// look for a `.git` folder in `path` or one of its ancestors
+pub fn find_git_root(path: &str) -> Option<PathBuf> {
+    let mut current = Path::new(path);
+    loop {
+        if current.join(".git").exists() {
+            return Some(current.to_path_buf());
+        }
+        current = current.parent()?;
+    }
+}
+
+/// stage everything in `path` and commit it, only if `path` is inside a git repository
+pub fn git_commit_kata(path: &str, message: &str) -> Result<(), String> {
+    if find_git_root(path).is_none() {
+        return Err("not a git repository".to_string());
+    }
+
+    let run_git = |args: &[&str]| -> Result<(), String> {
+        match Command::new("git").args(args).current_dir(path).output() {
+            Ok(out) if out.status.success() => Ok(()),
+            Ok(out) => Err(String::from_utf8_lossy(&out.stderr).trim().to_string()),
+            Err(why) => Err(why.to_string()),
+        }
     };
+
+    run_git(&["add", "--", "."])?;
+    run_git(&["commit", "-m", message, "--", "."])
 }
 
 pub fn ls_dir(path: &str) -> Result<Vec<String>, String> {
@@ -86,6 +361,104 @@ pub fn ls_dir(path: &str) -> Result<Vec<String>, String> {
     };
 }
 
+/// directories first, then alphabetically (case-insensitive), at most `limit` suggestions.
+/// `entries` are `(is_dir, name)`
+pub fn sort_path_suggestions(mut entries: Vec<(bool, String)>, limit: usize) -> Vec<String> {
+    entries.sort_by(|(a_is_dir, a), (b_is_dir, b)| {
+        b_is_dir
+            .cmp(a_is_dir)
+            .then_with(|| a.to_lowercase().cmp(&b.to_lowercase()))
+    });
+
+    entries
+        .into_iter()
+        .take(limit)
+        .map(|(_, name)| name)
+        .collect::<Vec<String>>()
+}
+
+/// bytes count for humans (e.g: "1.5 KB")
+pub fn human_size(bytes: u64) -> String {
+    let units = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < units.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{size:.1} {}", units[unit])
+    }
+}
+
+/// expand a leading `~` and the `$VAR`/`${VAR}` environment variables of a path,
+/// undefined variables are left as is
+pub fn expand_path(path: &str) -> String {
+    let mut expanded = match path.strip_prefix("~") {
+        Some(rest) if rest.len() <= 0 || rest.starts_with("/") => {
+            let home = match dirs::home_dir() {
+                Some(home) => home.to_string_lossy().to_string(),
+                None => format!("/home/{}", get_uname()),
+            };
+            home + rest
+        }
+        _ => path.to_string(),
+    };
+
+    let mut out = String::new();
+    while let Some(start) = expanded.find("$") {
+        out.push_str(&expanded[..start]);
+        let rest = &expanded[start + 1..];
+
+        let (name, len) = if let Some(braced) = rest.strip_prefix("{") {
+            match braced.find("}") {
+                Some(end) => (&braced[..end], end + 2),
+                None => ("", 0),
+            }
+        } else {
+            let end = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            (&rest[..end], end)
+        };
+
+        match std::env::var(name) {
+            Ok(value) if name.len() > 0 => out.push_str(&value),
+            _ => out.push_str(&expanded[start..start + 1 + len]),
+        }
+        expanded = rest[len..].to_string();
+    }
+    out.push_str(&expanded);
+    out
+}
+
+/// seconds since the unix epoch
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// "YYYY-MM-DD" (UTC) of a unix timestamp
+pub fn format_date(timestamp: u64) -> String {
+    // days to civil date, see http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let days = (timestamp / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
 pub fn get_uname() -> String {
     return get_current_username()
         .unwrap_or_default()
@@ -94,17 +467,45 @@ pub fn get_uname() -> String {
         .to_string();
 }
 
-pub fn log_print(log: String) {
-    let uname = get_uname();
+// token of the settings file, known here to scrub it from the logs
+static SETTINGS_TOKEN: RwLock<String> = RwLock::new(String::new());
 
-    let path_str = format!("/home/{uname}/.cache/codewars_cli");
-    let path = Path::new(path_str.as_str());
-    if let Err(_) = fs::create_dir_all(path) {
-        return;
+/// scrub `api_token` of the settings from the logs, on each load of the settings
+pub fn register_settings_token(settings_token: &str) {
+    if let Ok(mut token) = SETTINGS_TOKEN.write() {
+        *token = settings_token.trim().to_string();
     }
+}
 
-    let log_file = format!("{path_str}/dev_logs.log");
-    let log_file_path = Path::new(log_file.as_str());
+/// codewars api token: the `CODEWARS_TOKEN` env variable first, then `api_token` of the settings
+pub fn codewars_token(settings_token: &str) -> Option<String> {
+    match std::env::var("CODEWARS_TOKEN") {
+        Ok(token) if token.trim().len() > 0 => Some(token.trim().to_string()),
+        _ if settings_token.trim().len() > 0 => Some(settings_token.trim().to_string()),
+        _ => None,
+    }
+}
+
+/// hide the tokens in a text going to the logs
+pub fn scrub_tokens(text: &str) -> String {
+    let mut tokens = vec![std::env::var("CODEWARS_TOKEN").unwrap_or_default()];
+    if let Ok(token) = SETTINGS_TOKEN.read() {
+        tokens.push(token.to_owned());
+    }
+
+    let mut scrubbed = text.to_string();
+    for token in tokens.iter().map(|t| t.trim()).filter(|t| t.len() > 0) {
+        scrubbed = scrubbed.replace(token, "[token]");
+    }
+    scrubbed
+}
+
+pub fn log_print(log: String) {
+    let log = scrub_tokens(&log);
+    let log_file_path = match paths::log_file() {
+        Ok(path) => path,
+        Err(_) => return,
+    };
 
     let mut file = match OpenOptions::new()
         .create(true)
@@ -119,17 +520,145 @@ pub fn log_print(log: String) {
     if let Err(_) = writeln!(file, "{log}") {}
 }
 
-fn is_valid_url(s: &str) -> bool {
+/// last `max_lines` lines of the log file, none when nothing was logged yet
+pub fn read_log_tail(max_lines: usize) -> Result<Vec<String>, String> {
+    let content = match fs::read_to_string(paths::log_file()?) {
+        Ok(content) => content,
+        Err(why) if why.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(why) => return Err(why.to_string()),
+    };
+    let lines: Vec<String> = content.lines().map(|l| l.to_string()).collect();
+    Ok(lines[lines.len().saturating_sub(max_lines)..].to_vec())
+}
+
+/// empty the log file
+pub fn clear_log() -> Result<(), String> {
+    match File::create(paths::log_file()?) {
+        Ok(_) => Ok(()),
+        Err(why) => Err(why.to_string()),
+    }
+}
+
+/// move a file that can't be parsed (e.g: cut while written) to `<name>.bak`, so that it isn't written over. Returns the backup path
+pub fn backup_corrupt_file(path: &Path) -> Result<PathBuf, String> {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".bak");
+    let backup = path.with_file_name(name);
+
+    if let Err(why) = fs::rename(path, &backup) {
+        return Err(why.to_string());
+    }
+    log_print(format!(
+        "corrupt file {} moved to {}",
+        path.display(),
+        backup.display()
+    ));
+    Ok(backup)
+}
+
+/// where copied content landed
+pub enum CopyOutcome {
+    Clipboard,
+    TempFile(String), // no clipboard (headless server, some wayland setups), path of the file
+}
+
+/// copy to the clipboard, fallback to a `fallback_name` file in the temp dir when there is no clipboard.
+/// `clipboard` must outlive the copy: on X11 its owner serves the content, it's gone once dropped
+pub fn copy_to_clipboard(
+    clipboard: &mut Option<Clipboard>,
+    content: &str,
+    fallback_name: &str,
+) -> Result<CopyOutcome, String> {
+    if clipboard.is_none() {
+        *clipboard = Clipboard::new().ok();
+    }
+    let copied = match clipboard {
+        Some(clipboard) => clipboard.set_text(content.to_string()).is_ok(),
+        None => false,
+    };
+    if copied {
+        return Ok(CopyOutcome::Clipboard);
+    }
+
+    let tmp_path = std::env::temp_dir().join(fallback_name);
+    let tmp_path = tmp_path.to_string_lossy().to_string();
+    write_file(tmp_path.to_owned(), content.to_string())?;
+    return Ok(CopyOutcome::TempFile(tmp_path));
+}
+
+/// why a request to codewars failed
+#[derive(Debug)]
+pub enum FetchError {
+    NotFound,
+    Network(String),
+    Parse(String),
+    RateLimited,
+    Timeout,
+    LoginRequired, // redirected to the sign in page
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FetchError::NotFound => write!(f, "not found"),
+            FetchError::Network(why) => write!(f, "network error: {why}"),
+            FetchError::Parse(why) => write!(f, "unexpected response: {why}"),
+            FetchError::RateLimited => write!(f, "too many requests, try again later"),
+            FetchError::Timeout => write!(f, "request timed out"),
+            FetchError::LoginRequired => write!(f, "codewars requires to be logged in"),
+        }
+    }
+}
+
+impl Error for FetchError {}
+
+impl From<reqwest::Error> for FetchError {
+    fn from(err: reqwest::Error) -> Self {
+        if err.is_timeout() {
+            return FetchError::Timeout;
+        }
+        match err.status() {
+            Some(StatusCode::NOT_FOUND) => FetchError::NotFound,
+            Some(StatusCode::TOO_MANY_REQUESTS) => FetchError::RateLimited,
+            _ if err.is_decode() => FetchError::Parse(err.to_string()),
+            _ => FetchError::Network(err.to_string()),
+        }
+    }
+}
+
+impl From<serde_json::Error> for FetchError {
+    fn from(err: serde_json::Error) -> Self {
+        FetchError::Parse(err.to_string())
+    }
+}
+
+pub fn is_valid_url(s: &str) -> bool {
     Url::parse(s).is_ok()
 }
 
-pub async fn fetch_html(url: String) -> Result<String, Box<dyn Error>> {
+pub async fn fetch_html(url: String) -> Result<String, FetchError> {
     if !is_valid_url(url.as_str()) {
-        return Err("invalid url".into());
+        return Err(FetchError::Network(format!("invalid url {url}")));
     }
 
-    let resp = reqwest::get(url).await?.text().await?;
-    Ok(resp)
+    let resp = reqwest::get(url).await?.error_for_status()?;
+    // restricted pages redirect to the sign in one, which has no katas: not an empty search
+    if resp.url().path().starts_with("/users/sign_in") {
+        return Err(FetchError::LoginRequired);
+    }
+    let html = resp.text().await?;
+    if is_login_page(&html) {
+        return Err(FetchError::LoginRequired);
+    }
+    Ok(html)
+}
+
+/// sign in page of codewars, served in place of the restricted ones
+pub fn is_login_page(html_doc: &str) -> bool {
+    let document = Html::parse_document(html_doc);
+    let selector =
+        Selector::parse("form[action*=\"sign_in\"], input[name=\"user[password]\"]").unwrap();
+    document.select(&selector).next().is_some()
 }
 
 // scraper::element_ref::Text hijack to add some methods
@@ -162,18 +691,244 @@ pub fn write_file(path_str: String, value: String) -> Result<(), String> {
     }
 }
 
-pub async fn fetch_codewars_api(kata_id: &str) -> Result<KataAPI, reqwest::Error> {
-    // get instruction
-    let api_resp = reqwest::get(format!(
-        "https://www.codewars.com/api/v1/code-challenges/{}",
+/// push the katas of `new_katas` that aren't in `katas` yet (by id, the first occurrence wins), returns how many were pushed
+pub fn push_unique_katas(katas: &mut Vec<KataAPI>, new_katas: Vec<KataAPI>) -> usize {
+    let mut ids: HashSet<String> = katas.iter().map(|k| k.id.to_owned()).collect();
+    let count = katas.len();
+    for kata in new_katas {
+        if ids.insert(kata.id.to_owned()) {
+            katas.push(kata);
+        }
+    }
+    katas.len() - count
+}
+
+/// markdown table of katas (name, rank, author, completions, tags, url)
+pub fn katas_to_markdown(katas: &[KataAPI]) -> String {
+    let escape = |s: &str| s.replace("|", "\\|");
+
+    let mut table = vec![
+        "| Name | Rank | Author | Completed | Tags | URL |".to_string(),
+        "| --- | --- | --- | --- | --- | --- |".to_string(),
+    ];
+    for kata in katas {
+        table.push(format!(
+            "| {} | {} | {} | {} | {} | {} |",
+            escape(&kata.name),
+            escape(&kata.rank.name),
+            escape(&kata.createdBy.username),
+            kata.totalCompleted,
+            escape(&kata.tags.join(", ")),
+            kata.url
+        ));
+    }
+    table.join("\n") + "\n"
+}
+
+/// same as `katas_to_markdown` but as csv
+pub fn katas_to_csv(katas: &[KataAPI]) -> String {
+    let quote = |s: &str| format!("\"{}\"", s.replace("\"", "\"\""));
+
+    let mut rows = vec!["name,rank,author,completed,tags,url".to_string()];
+    for kata in katas {
+        rows.push(format!(
+            "{},{},{},{},{},{}",
+            quote(&kata.name),
+            quote(&kata.rank.name),
+            quote(&kata.createdBy.username),
+            kata.totalCompleted,
+            quote(&kata.tags.join(", ")),
+            quote(&kata.url)
+        ));
+    }
+    rows.join("\n") + "\n"
+}
+
+/// codewars host, can be overridden with `CODEWARS_BASE_URL` (e.g: a local server for tests, a proxy)
+pub fn codewars_base_url() -> String {
+    match std::env::var("CODEWARS_BASE_URL") {
+        Ok(url) if url.trim().len() > 0 => url.trim().trim_end_matches("/").to_string(),
+        _ => CODEWARS_URL.to_string(),
+    }
+}
+
+/// canonical url of a kata (`/kata/{id}/{slug}`), only the id when the slug isn't known
+pub fn kata_url(kata_id: &str, slug: &str) -> String {
+    if slug.is_empty() {
+        return format!("{}/kata/{kata_id}", codewars_base_url());
+    }
+    format!("{}/kata/{kata_id}/{slug}", codewars_base_url())
+}
+
+/// `token` authenticates the request, see `codewars_token`
+pub async fn fetch_codewars_api(kata_id: &str, token: Option<&str>) -> Result<KataAPI, FetchError> {
+    let mut request = reqwest::Client::new().get(format!(
+        "{}/api/v1/code-challenges/{}",
+        codewars_base_url(),
         kata_id
-    ))
-    .await?
-    .json::<KataAPI>()
-    .await?;
+    ));
+    if let Some(token) = token {
+        request = request.header(reqwest::header::AUTHORIZATION, token);
+    }
+
+    // get instruction
+    let mut api_resp = request
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<KataAPI>()
+        .await?;
+    // approved katas get a rank, beta ones don't
+    api_resp.beta = api_resp.rank.name.len() <= 0;
     return Ok(api_resp);
 }
 
+/// katas saved for offline reading by id, empty when none were saved
+pub fn offline_katas() -> HashMap<String, KataAPI> {
+    match paths::data_file(OFFLINE_KATAS_FILE) {
+        Ok(path) => read_offline_katas(&path),
+        Err(_) => HashMap::new(),
+    }
+}
+
+fn read_offline_katas(path: &Path) -> HashMap<String, KataAPI> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(_) => return HashMap::new(),
+    };
+    match serde_json::from_str(&content) {
+        Ok(katas) => katas,
+        // kept aside, the next save would write over it
+        Err(_) => {
+            if let Err(_) = backup_corrupt_file(path) {}
+            HashMap::new()
+        }
+    }
+}
+
+/// replace the katas saved for offline reading
+pub fn save_offline_katas(katas: &HashMap<String, KataAPI>) -> Result<(), String> {
+    write_offline_katas(&paths::data_file(OFFLINE_KATAS_FILE)?, katas)
+}
+
+fn write_offline_katas(path: &Path, katas: &HashMap<String, KataAPI>) -> Result<(), String> {
+    match serde_json::to_string(katas) {
+        Ok(content) => write_file(path.to_string_lossy().to_string(), content),
+        Err(why) => Err(why.to_string()),
+    }
+}
+
+/// the kata saved for offline reading when codewars can't be reached, `offline` is only read then
+pub fn offline_fallback(
+    fetched: Result<KataAPI, FetchError>,
+    kata_id: &str,
+    offline: impl FnOnce() -> HashMap<String, KataAPI>,
+) -> Result<KataAPI, FetchError> {
+    match fetched {
+        Err(why @ (FetchError::Network(_) | FetchError::Timeout)) => {
+            match offline().remove(kata_id) {
+                Some(kata) => Ok(kata),
+                None => Err(why),
+            }
+        }
+        fetched => fetched,
+    }
+}
+
+/// authored or trained katas of the codewars user `username`, the trained ones are paginated: only the last 200
+pub async fn fetch_user_katas(
+    username: &str,
+    kind: UserKatas,
+    token: Option<&str>,
+) -> Result<Vec<KataAPI>, FetchError> {
+    let mut request = reqwest::Client::new().get(format!(
+        "{}/api/v1/users/{}/code-challenges/{}",
+        codewars_base_url(),
+        encode(username),
+        kind.endpoint()
+    ));
+    if let Some(token) = token {
+        request = request.header(reqwest::header::AUTHORIZATION, token);
+    }
+
+    let api_resp = request
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<APIUserKatas>()
+        .await?;
+    return Ok(user_katas(api_resp, kind));
+}
+
+/// katas of a users api list, with what the list gives
+pub fn user_katas(api_resp: APIUserKatas, kind: UserKatas) -> Vec<KataAPI> {
+    api_resp
+        .data
+        .into_iter()
+        .map(|kata| KataAPI {
+            url: kata_url(&kata.id, &kata.slug),
+            // the trained languages until the kata ones are fetched (download modal)
+            languages: if kata.languages.len() > 0 {
+                kata.languages
+            } else {
+                kata.completedLanguages
+            },
+            // the trained list has no rank
+            beta: kind == UserKatas::Authored && kata.rankName.len() <= 0,
+            rank: APIRank {
+                name: kata.rankName,
+                ..APIRank::default()
+            },
+            id: kata.id,
+            name: kata.name,
+            slug: kata.slug,
+            description: kata.description,
+            tags: kata.tags,
+            ..KataAPI::default()
+        })
+        .collect()
+}
+
+/// render a markdown document (kata instruction) as html
+pub fn markdown_to_html(markdown: &str) -> String {
+    let mut html_out = String::new();
+    html::push_html(&mut html_out, Parser::new(markdown));
+    html_out
+}
+
+/// strip the markdown syntax of a document, keeping the text and the line breaks
+pub fn markdown_to_text(markdown: &str) -> String {
+    let mut text = String::new();
+    for event in Parser::new(markdown) {
+        match event {
+            Event::Text(t) | Event::Code(t) => text.push_str(&t),
+            Event::SoftBreak | Event::HardBreak => text.push('\n'),
+            Event::Start(Tag::Item) => text.push_str("- "),
+            Event::End(Tag::Paragraph | Tag::Heading(..) | Tag::CodeBlock(_)) => {
+                text.push_str("\n\n")
+            }
+            Event::End(Tag::Item) => text.push('\n'),
+            Event::Rule => text.push_str("---\n\n"),
+            _ => {}
+        }
+    }
+    text.trim().to_string() + "\n"
+}
+
+/// kata ids of a newline-delimited list, a line can also be a kata url. Empty lines and `#` comments are skipped
+pub fn parse_kata_ids(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| line.len() > 0 && !line.starts_with("#"))
+        .map(|line| match line.split_once("/kata/") {
+            Some((_, rest)) => rest.split("/").next().unwrap_or_default().to_string(),
+            None => line.to_string(),
+        })
+        .filter(|id| id.len() > 0)
+        .collect()
+}
+
 // yet a another utils func
 
 pub fn language_to_extension(language: &str) -> Option<&str> {
@@ -239,3 +994,469 @@ pub fn language_to_extension(language: &str) -> Option<&str> {
         _ => None,
     }
 }
+
+/// minimal .gitignore for the build artifacts of a language, None when it has none
+pub fn gitignore_for(language: &str) -> Option<&str> {
+    match language {
+        "rust" => Some("target/\n"),
+        "javascript" | "typescript" | "coffeescript" | "purescript" | "reason" => {
+            Some("node_modules/\n")
+        }
+        "python" => Some("__pycache__/\n*.pyc\n.venv/\n"),
+        "java" | "kotlin" | "groovy" | "scala" => Some("*.class\nbuild/\ntarget/\n.gradle/\n"),
+        "c" | "cpp" | "nasm" | "fortran" => Some("*.o\n*.out\nbuild/\n"),
+        "csharp" | "fsharp" | "vb" => Some("bin/\nobj/\n"),
+        "go" => Some("*.test\n"),
+        "haskell" => Some("dist-newstyle/\n.stack-work/\n"),
+        "elixir" => Some("_build/\ndeps/\n"),
+        "erlang" => Some("_build/\n*.beam\n"),
+        "dart" => Some(".dart_tool/\nbuild/\n"),
+        "swift" => Some(".build/\n"),
+        "ocaml" => Some("_build/\n"),
+        "crystal" => Some("lib/\n.crystal/\n"),
+        "julia" => Some("Manifest.toml\n"),
+        "elm" => Some("elm-stuff/\n"),
+        "racket" => Some("compiled/\n"),
+        _ => None,
+    }
+}
+
+/// codewars slug of a language name of LANGAGE (e.g: "C++" is "cpp")
+pub fn language_slug(display: &str) -> String {
+    match display {
+        "C++" => "cpp".to_string(),
+        "Objective-C" => "objc".to_string(),
+        "C#" => "csharp".to_string(),
+        "F#" => "fsharp".to_string(),
+        "λ Calculus" => "lambdacalc".to_string(),
+        "RISC-V" => "riscv".to_string(),
+        l => l.to_lowercase().trim().replace(" ", "-"),
+    }
+}
+
+/// name of a codewars language slug as in LANGAGE (e.g: "cpp" is "C++"), the slug itself when unknown
+pub fn language_display(slug: &str) -> &str {
+    // the first two are "All" and "My Languages"
+    match LANGAGE[2..].iter().find(|l| language_slug(l) == slug) {
+        Some(display) => display,
+        None => slug,
+    }
+}
+
+/// line comment token of a language (codewars slug)
+pub fn comment_prefix(language: &str) -> &str {
+    match language {
+        "python" | "ruby" | "shell" | "r" | "julia" | "perl" | "powershell" | "coffeescript"
+        | "crystal" | "elixir" | "nim" | "raku" | "lambdacalc" => "#",
+        "haskell" | "lua" | "sql" | "elm" | "idris" | "agda" | "purescript" | "lean" => "--",
+        "commonlisp" | "clojure" | "racket" | "nasm" | "riscv" => ";",
+        "erlang" | "prolog" => "%",
+        "fortran" => "!",
+        "vb" => "'",
+        "factor" | "forth" => "\\",
+        "cobol" => "*>",
+        _ => "//",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // codewars slugs of the LANGAGE filter (without "All" and "My Languages")
+    const SLUGS: [&str; 58] = [
+        "agda",
+        "bf",
+        "c",
+        "cfml",
+        "clojure",
+        "cobol",
+        "coffeescript",
+        "commonlisp",
+        "coq",
+        "cpp",
+        "crystal",
+        "csharp",
+        "d",
+        "dart",
+        "elixir",
+        "elm",
+        "erlang",
+        "factor",
+        "forth",
+        "fortran",
+        "fsharp",
+        "go",
+        "groovy",
+        "haskell",
+        "haxe",
+        "idris",
+        "java",
+        "javascript",
+        "julia",
+        "kotlin",
+        "lambdacalc",
+        "lean",
+        "lua",
+        "nasm",
+        "nim",
+        "objc",
+        "ocaml",
+        "pascal",
+        "perl",
+        "php",
+        "powershell",
+        "prolog",
+        "purescript",
+        "python",
+        "r",
+        "racket",
+        "raku",
+        "reason",
+        "riscv",
+        "ruby",
+        "rust",
+        "scala",
+        "shell",
+        "solidity",
+        "sql",
+        "swift",
+        "typescript",
+        "vb",
+    ];
+
+    #[test]
+    fn sanitize_query_trims_whitespaces() {
+        assert_eq!(sanitize_query(" foo ", 100), sanitize_query("foo", 100));
+        assert_eq!(
+            sanitize_query("\t foo bar\n", 100),
+            ("foo bar".to_string(), false)
+        );
+        assert_eq!(sanitize_query("   ", 100), (String::new(), false));
+    }
+
+    #[test]
+    fn sanitize_query_strips_control_chars() {
+        assert_eq!(
+            sanitize_query("fo\u{7}o\u{1b}", 100),
+            ("foo".to_string(), false)
+        );
+        assert_eq!(sanitize_query("a\rb\0c", 100), ("abc".to_string(), false));
+    }
+
+    #[test]
+    fn sanitize_query_truncates() {
+        assert_eq!(sanitize_query("abcdef", 3), ("abc".to_string(), true));
+        assert_eq!(sanitize_query("λλλλ", 4), ("λλλλ".to_string(), false));
+    }
+
+    #[test]
+    fn diff_lines_works() {
+        assert_eq!(
+            diff_lines("fn a() {\n    1\n}", "fn a() {\n    2\n}\n"),
+            vec![
+                DiffLine::Same("fn a() {".to_string()),
+                DiffLine::Removed("    1".to_string()),
+                DiffLine::Added("    2".to_string()),
+                DiffLine::Same("}".to_string()),
+            ]
+        );
+        assert_eq!(diff_lines("", "a"), vec![DiffLine::Added("a".to_string())]);
+        assert!(diff_lines("a\nb", "a\nb")
+            .iter()
+            .all(|l| matches!(l, DiffLine::Same(_))));
+    }
+
+    #[test]
+    fn normalize_code_lines_keeps_indentation() {
+        let scraped = [
+            "\u{200b}",
+            "def solution(s):  \r\n",
+            "\u{a0}\u{a0}\u{a0}\u{a0}if not s:\n",
+            "        return []\t",
+            "\u{200b}\n",
+            "    return s.split()",
+            "",
+            "\u{200b}",
+        ];
+        assert_eq!(
+            normalize_code_lines(scraped.iter().map(|l| l.to_string()).collect()),
+            vec![
+                "def solution(s):",
+                "    if not s:",
+                "        return []",
+                "",
+                "    return s.split()",
+            ]
+        );
+        assert!(normalize_code_lines(vec!["\u{200b}".to_string()]).is_empty());
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn platform_openers_fallbacks() {
+        let openers = platform_openers("https://www.codewars.com", "firefox --new-tab %s:w3m");
+        let url = "https://www.codewars.com".to_string();
+        assert_eq!(
+            openers,
+            vec![
+                ("xdg-open".to_string(), vec![url.clone()]),
+                (
+                    "firefox".to_string(),
+                    vec!["--new-tab".to_string(), url.clone()]
+                ),
+                ("w3m".to_string(), vec![url.clone()]),
+                ("gio".to_string(), vec!["open".to_string(), url.clone()]),
+            ]
+        );
+
+        // without $BROWSER
+        assert_eq!(platform_openers("/tmp", "").len(), 2);
+    }
+
+    #[test]
+    fn user_katas_from_the_api() {
+        let authored = serde_json::from_str::<APIUserKatas>(
+            r#"{"data": [
+                {"id": "5277c8a221e209d3f6000b56", "name": "Valid Braces", "description": "...",
+                 "rank": -4, "rankName": "4 kyu", "tags": ["Algorithms"], "languages": ["rust"]},
+                {"id": "64a1b2c3d4e5f60718293a4b", "name": "Draft", "rank": null, "rankName": null}
+            ]}"#,
+        )
+        .unwrap();
+        let katas = user_katas(authored, UserKatas::Authored);
+        assert_eq!(katas[0].rank.name, "4 kyu");
+        assert_eq!(katas[0].languages, vec!["rust"]);
+        assert!(!katas[0].beta);
+        assert!(katas[1].beta);
+
+        let trained = serde_json::from_str::<APIUserKatas>(
+            r#"{"totalPages": 1, "totalItems": 1, "data": [
+                {"id": "5277c8a221e209d3f6000b56", "name": "Valid Braces", "slug": "valid-braces",
+                 "completedLanguages": ["python", "rust"], "completedAt": "2023-01-01T00:00:00Z"}
+            ]}"#,
+        )
+        .unwrap();
+        let katas = user_katas(trained, UserKatas::Trained);
+        assert_eq!(katas[0].languages, vec!["python", "rust"]);
+        assert!(katas[0]
+            .url
+            .ends_with("/kata/5277c8a221e209d3f6000b56/valid-braces"));
+        assert!(!katas[0].beta);
+    }
+
+    #[test]
+    fn login_page_detection() {
+        let sign_in = r#"<html><head><title>Sign in | Codewars</title></head><body>
+            <form class="new_user" action="/users/sign_in" method="post">
+            <input type="email" name="user[email]"><input type="password" name="user[password]">
+            </form></body></html>"#;
+        assert!(is_login_page(sign_in));
+
+        let search = r#"<html><body><main><div class="list-item-kata">
+            <a href="/kata/5277c8a221e209d3f6000b56">Valid Braces</a></div></main></body></html>"#;
+        assert!(!is_login_page(search));
+        assert!(!is_login_page(""));
+    }
+
+    #[test]
+    fn tokens_are_scrubbed() {
+        register_settings_token("s3cr3t-settings-token");
+        assert_eq!(
+            scrub_tokens("GET /api with s3cr3t-settings-token"),
+            "GET /api with [token]"
+        );
+        assert_eq!(scrub_tokens("nothing to hide"), "nothing to hide");
+    }
+
+    #[test]
+    fn seeded_colors_are_reproducible() {
+        let (mut a, mut b) = (SeededColors::new(42), SeededColors::new(42));
+        let colors = (0..3).map(|_| a.next_color()).collect::<Vec<Color>>();
+        assert_eq!(
+            colors,
+            (0..3).map(|_| b.next_color()).collect::<Vec<Color>>()
+        );
+        assert_eq!(FixedColors(Color::Red).next_color(), Color::Red);
+    }
+
+    #[test]
+    fn kata_id_detection() {
+        assert!(is_kata_id("5277c8a221e209d3f6000b56"));
+        assert!(!is_kata_id("binary search tree basics")); // 24 chars with spaces
+        assert!(!is_kata_id("abcdefghijklmnopqrstuvwx"));
+        assert!(!is_kata_id("5277c8a221e209d3f6000b5"));
+    }
+
+    #[test]
+    fn expand_path_home() {
+        let home = dirs::home_dir().unwrap().to_string_lossy().to_string();
+        assert_eq!(expand_path("~"), home);
+        assert_eq!(expand_path("~/katas"), format!("{home}/katas"));
+        assert_eq!(expand_path("/tmp/~katas"), "/tmp/~katas"); // only a leading ~
+    }
+
+    #[test]
+    fn expand_path_env_vars() {
+        std::env::set_var("CODEWARS_CLI_TEST_WORKSPACE", "/work");
+        assert_eq!(
+            expand_path("$CODEWARS_CLI_TEST_WORKSPACE/katas"),
+            "/work/katas"
+        );
+        assert_eq!(
+            expand_path("${CODEWARS_CLI_TEST_WORKSPACE}/katas"),
+            "/work/katas"
+        );
+        assert_eq!(
+            expand_path("$HOME/katas"),
+            format!("{}/katas", std::env::var("HOME").unwrap())
+        );
+    }
+
+    #[test]
+    fn expand_path_undefined_var() {
+        assert_eq!(
+            expand_path("$CODEWARS_CLI_UNDEFINED/katas"),
+            "$CODEWARS_CLI_UNDEFINED/katas"
+        );
+        assert_eq!(
+            expand_path("${CODEWARS_CLI_UNDEFINED}/katas"),
+            "${CODEWARS_CLI_UNDEFINED}/katas"
+        );
+        assert_eq!(expand_path("/tmp/${unclosed"), "/tmp/${unclosed");
+        assert_eq!(expand_path("/tmp/price$"), "/tmp/price$");
+    }
+
+    #[test]
+    fn format_date_utc() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(951782400), "2000-02-29");
+        assert_eq!(format_date(1700000000), "2023-11-14");
+    }
+
+    #[test]
+    fn language_to_extension_mappings() {
+        assert_eq!(language_to_extension("rust"), Some(".rs"));
+        assert_eq!(language_to_extension("cpp"), Some(".cpp"));
+        assert_eq!(language_to_extension("csharp"), Some(".cs"));
+        assert_eq!(language_to_extension("typescript"), Some(".ts"));
+        assert_eq!(language_to_extension("riscv"), Some(".s"));
+    }
+
+    #[test]
+    fn language_to_extension_unknown() {
+        assert_eq!(language_to_extension("brainfuck++"), None);
+        assert_eq!(language_to_extension(""), None);
+        assert_eq!(language_to_extension("Rust"), None); // slugs only
+    }
+
+    #[test]
+    fn language_to_extension_complete() {
+        for slug in SLUGS {
+            assert!(
+                language_to_extension(slug).is_some(),
+                "no extension for {slug}"
+            );
+        }
+    }
+
+    #[test]
+    fn language_slug_display_round_trip() {
+        assert_eq!(LANGAGE.len() - 2, SLUGS.len());
+        for display in &LANGAGE[2..] {
+            assert_eq!(language_display(&language_slug(display)), *display);
+        }
+        for slug in SLUGS {
+            assert_ne!(language_display(slug), slug, "no name for {slug}");
+            assert_eq!(language_slug(language_display(slug)), slug);
+        }
+        assert_eq!(language_slug("C++"), "cpp");
+        assert_eq!(language_display("lambdacalc"), "λ Calculus");
+        assert_eq!(language_display("not-a-language"), "not-a-language");
+    }
+
+    #[test]
+    fn language_to_extension_round_trip() {
+        // an extension leads back to a single language, except the known ambiguous ones
+        let ambiguous = [".pl"]; // perl & prolog
+        for slug in SLUGS {
+            let ext = language_to_extension(slug).unwrap();
+            let owners = SLUGS
+                .iter()
+                .filter(|s| language_to_extension(s) == Some(ext))
+                .collect::<Vec<_>>();
+            if !ambiguous.contains(&ext) {
+                assert_eq!(owners, vec![&slug], "{ext} is shared by {owners:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn offline_katas_keep_the_details() {
+        let dir = std::env::temp_dir().join(format!("codewars_cli_offline_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(OFFLINE_KATAS_FILE);
+        assert!(read_offline_katas(&path).is_empty());
+
+        let mut kata = KataAPI::default();
+        kata.id = "k1".to_string();
+        kata.description = "# Instruction".to_string();
+        kata.rank.name = "6 kyu".to_string();
+        kata.createdBy.username = "someone".to_string();
+        let mut katas = HashMap::new();
+        katas.insert(kata.id.to_owned(), kata);
+        write_offline_katas(&path, &katas).unwrap();
+
+        let read = read_offline_katas(&path);
+        let kata = &read["k1"];
+        assert_eq!(kata.description, "# Instruction");
+        assert_eq!(kata.rank.name, "6 kyu");
+        assert_eq!(kata.createdBy.username, "someone");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn offline_fallback_when_codewars_is_unreachable() {
+        let mut kata = KataAPI::default();
+        kata.id = "k1".to_string();
+        kata.description = "# Instruction".to_string();
+        let saved = || HashMap::from([("k1".to_string(), kata.clone())]);
+
+        let fallback = offline_fallback(Err(FetchError::Network("down".to_string())), "k1", saved);
+        assert_eq!(fallback.unwrap().description, "# Instruction");
+        let fallback = offline_fallback(Err(FetchError::Timeout), "k1", saved);
+        assert_eq!(fallback.unwrap().description, "# Instruction");
+
+        // not saved
+        let fallback = offline_fallback(Err(FetchError::Timeout), "k2", saved);
+        assert!(matches!(fallback, Err(FetchError::Timeout)));
+        // codewars answered: the saved katas aren't read
+        let fallback = offline_fallback(Err(FetchError::NotFound), "k1", || {
+            panic!("read the offline katas")
+        });
+        assert!(matches!(fallback, Err(FetchError::NotFound)));
+        let fetched = offline_fallback(Ok(KataAPI::default()), "k1", || {
+            panic!("read the offline katas")
+        });
+        assert_eq!(fetched.unwrap().description, "");
+    }
+
+    #[test]
+    fn katas_markdown_table() {
+        let mut kata = KataAPI::default();
+        kata.name = "Pipes | and more".to_string();
+        kata.rank.name = "6 kyu".to_string();
+        kata.createdBy.username = "someone".to_string();
+        kata.totalCompleted = 42;
+        kata.tags = vec!["Strings".to_string(), "a|b".to_string()];
+        kata.url = "https://www.codewars.com/kata/k1".to_string();
+
+        assert_eq!(
+            katas_to_markdown(&[kata]),
+            "| Name | Rank | Author | Completed | Tags | URL |\n\
+             | --- | --- | --- | --- | --- | --- |\n\
+             | Pipes \\| and more | 6 kyu | someone | 42 | Strings, a\\|b | https://www.codewars.com/kata/k1 |\n"
+        );
+        assert_eq!(katas_to_markdown(&[]).lines().count(), 2);
+    }
+}